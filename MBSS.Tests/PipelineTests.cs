@@ -0,0 +1,75 @@
+﻿using LibGit2Sharp;
+using Xunit;
+
+namespace MBSS.Tests;
+
+// The whole pipeline offline, a local depot stands in for Steam and a copying script for GenericStripper
+public class PipelineTests : IDisposable
+{
+    private readonly string _previousDirectory = Directory.GetCurrentDirectory();
+    private readonly string _root = Path.Combine(Path.GetTempPath(), $"mbss-tests-{Guid.NewGuid():N}");
+
+    public void Dispose()
+    {
+        Directory.SetCurrentDirectory(_previousDirectory);
+        if (!Directory.Exists(_root)) return;
+
+        // Git marks its objects read-only, which keeps Directory.Delete from removing them on Windows
+        foreach (var file in Directory.EnumerateFiles(_root, "*", SearchOption.AllDirectories))
+            File.SetAttributes(file, FileAttributes.Normal);
+        Directory.Delete(_root, true);
+    }
+
+    [Fact]
+    public async Task DownloadsStripsAndCommitsVersion()
+    {
+        var depot = Path.Combine(_root, "depots", "1.29.1");
+        Directory.CreateDirectory(Path.Combine(depot, "Beat Saber_Data", "Managed"));
+        await File.WriteAllTextAsync(Path.Combine(depot, "BeatSaberVersion.txt"), "1.29.1");
+        await File.WriteAllTextAsync(Path.Combine(depot, "Beat Saber_Data", "Managed", "Main.txt"), "stripped");
+
+        var repoPath = Path.Combine(_root, "repo");
+        Repository.Init(repoPath);
+        using (var repo = new Repository(repoPath)) repo.Refs.UpdateTarget("HEAD", "refs/heads/main");
+        await File.WriteAllTextAsync(Path.Combine(repoPath, "versions.json"),
+            "[{ \"version\": \"1.29.1\", \"manifest\": \"1234567890\" }]");
+
+        Environment.SetEnvironmentVariable("GIT_AUTHOR_NAME", "MBSS Tests");
+        Environment.SetEnvironmentVariable("GIT_AUTHOR_EMAIL", "tests@mbss.invalid");
+        Environment.SetEnvironmentVariable("MBSS_LOCAL_DEPOT_PATH", Path.Combine(_root, "depots"));
+        Environment.SetEnvironmentVariable("MBSS_GENERICSTRIPPER_PATH", await WriteStripper());
+        Environment.SetEnvironmentVariable("MBSS_TOOLS_DIR", Path.Combine(_root, "tools"));
+        Environment.SetEnvironmentVariable("MBSS_VERSION_SIZE_GB", "0");
+        Environment.SetEnvironmentVariable("MBSS_MIN_FREE_GB", "0");
+
+        // The workspace is wherever MBSS starts, just like running it from the root of a versions repository
+        Directory.SetCurrentDirectory(repoPath);
+        Environment.ExitCode = ExitCodes.Success;
+        await Program.Main(new[] { "--offline", "--skip-preflight" });
+
+        Assert.Equal(ExitCodes.Success, Environment.ExitCode);
+        using var result = new Repository(repoPath);
+        var tree = VersionTree.Find(result, "1.29.1");
+        Assert.NotNull(tree);
+        Assert.Equal("stripped", ((Blob)tree!["Beat Saber_Data/Managed/Main.txt"].Target).GetContentText());
+        Assert.Equal("1.29.1\n", ((Blob)tree["version.txt"].Target).GetContentText());
+        Assert.Equal("chore: v1.29.1", result.Head.Tip.MessageShort);
+    }
+
+    // Called as strip -m <mode> -p <depot> -o <output>, a plain copy is all the pipeline needs from it
+    private async Task<string> WriteStripper()
+    {
+        Directory.CreateDirectory(_root);
+        if (OperatingSystem.IsWindows())
+        {
+            var batch = Path.Combine(_root, "strip.cmd");
+            await File.WriteAllTextAsync(batch, "@xcopy %5 %7 /E /I /Y /Q\r\n");
+            return batch;
+        }
+
+        var script = Path.Combine(_root, "strip.sh");
+        await File.WriteAllTextAsync(script, "#!/bin/sh\nmkdir -p \"$7\" && cp -R \"$5\"/. \"$7\"\n");
+        File.SetUnixFileMode(script, UnixFileMode.UserRead | UnixFileMode.UserWrite | UnixFileMode.UserExecute);
+        return script;
+    }
+}
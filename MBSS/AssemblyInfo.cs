@@ -0,0 +1,28 @@
+﻿using System.Reflection.Metadata;
+using System.Reflection.PortableExecutable;
+
+namespace MBSS;
+
+internal static class AssemblyInfo
+{
+    public static string? GetVersion(Stream stream)
+    {
+        try
+        {
+            // PEReader needs a seekable stream, git blob streams aren't
+            using var buffer = new MemoryStream();
+            stream.CopyTo(buffer);
+            buffer.Position = 0;
+
+            using var pe = new PEReader(buffer);
+            if (!pe.HasMetadata) return null;
+
+            var metadata = pe.GetMetadataReader();
+            return metadata.IsAssembly ? metadata.GetAssemblyDefinition().Version.ToString() : null;
+        }
+        catch (BadImageFormatException)
+        {
+            return null;
+        }
+    }
+}
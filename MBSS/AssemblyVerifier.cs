@@ -0,0 +1,53 @@
+﻿using System.Reflection.Metadata;
+using System.Reflection.PortableExecutable;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal static class AssemblyVerifier
+{
+    public static async Task Verify(BeatSaberVersion version, string versionPath)
+    {
+        var invalid = new List<string>();
+        foreach (var file in Directory.EnumerateFiles(versionPath, "*.dll", SearchOption.AllDirectories))
+            if (!IsValidImage(file))
+                invalid.Add(Path.GetRelativePath(versionPath, file));
+
+        foreach (var file in invalid) AnsiConsole.MarkupLine($"[red]Invalid assembly: {file.EscapeMarkup()}[/]");
+        if (invalid.Count > 0)
+            throw new MbssException(ErrorKind.StripFailure,
+                $"Version {version.Version} contains {invalid.Count} invalid assemblies!");
+
+        var verifier = Environment.GetEnvironmentVariable("MBSS_VERIFY_COMMAND");
+        if (string.IsNullOrEmpty(verifier)) return;
+
+        var exitCode = await Shell.Run(verifier.Replace("{path}", versionPath),
+            Hooks.ContextEnvironment(version, versionPath));
+        if (exitCode != 0)
+            throw new MbssException(ErrorKind.ContentCheck,
+                $"Verifier failed for version {version.Version} with exit code {exitCode}!");
+    }
+
+    private static bool IsValidImage(string file)
+    {
+        try
+        {
+            using var stream = File.OpenRead(file);
+            using var pe = new PEReader(stream);
+            if (pe.PEHeaders.PEHeader == null) return false;
+
+            // Native plugins are valid PE images without CLI metadata
+            if (!pe.HasMetadata) return true;
+
+            var metadata = pe.GetMetadataReader();
+            if (metadata.IsAssembly) metadata.GetAssemblyDefinition();
+            foreach (var handle in metadata.TypeDefinitions) metadata.GetTypeDefinition(handle);
+
+            return true;
+        }
+        catch (BadImageFormatException)
+        {
+            return false;
+        }
+    }
+}
@@ -0,0 +1,27 @@
+﻿namespace MBSS;
+
+internal static class Assets
+{
+    public static string Read(string name)
+    {
+        // Files in the assets directory take precedence over the embedded ones, or replace them entirely
+        var directory = Options.AssetsDirectory;
+        if (!string.IsNullOrEmpty(directory))
+        {
+            var path = Path.Combine(directory, name);
+            if (File.Exists(path)) return File.ReadAllText(path);
+            if (Options.ReplaceAssets)
+                throw new MbssException(ErrorKind.Config, $"Asset {name} does not exist in {directory}!");
+        }
+
+        using var stream = typeof(Assets).Assembly.GetManifestResourceStream(name) ??
+                           throw new Exception($"Embedded asset {name} does not exist!");
+        using var reader = new StreamReader(stream);
+        return reader.ReadToEnd();
+    }
+
+    public static string Render(string name, Dictionary<string, string> values)
+    {
+        return values.Aggregate(Read(name), (text, value) => text.Replace($"{{{value.Key}}}", value.Value));
+    }
+}
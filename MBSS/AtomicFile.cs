@@ -0,0 +1,19 @@
+﻿namespace MBSS;
+
+internal static class AtomicFile
+{
+    // Readers either see the old contents or the new ones, never a file cut short by a crash
+    public static async Task WriteAllTextAsync(string path, string contents)
+    {
+        var temp = $"{path}.{Path.GetRandomFileName()}.tmp";
+        try
+        {
+            await File.WriteAllTextAsync(temp, contents);
+            File.Move(temp, path, true);
+        }
+        finally
+        {
+            if (File.Exists(temp)) File.Delete(temp);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+﻿using Newtonsoft.Json;
+
+namespace MBSS;
+
+internal class BeatSaberVersion
+{
+    [JsonProperty("version")] public string GameVersion { get; set; } = string.Empty;
+    [JsonProperty("manifest")] public string Manifest { get; set; } = string.Empty;
+
+    // windows, linux or macos, other platforms are shipped in their own depot with their own manifests
+    [JsonProperty("platform")] public string? Platform { get; set; }
+    [JsonProperty("depot")] public string? Depot { get; set; }
+    [JsonProperty("beta")] public string? Beta { get; set; }
+    [JsonProperty("beta_password")] public string? BetaPassword { get; set; }
+    [JsonProperty("stripper_mode")] public string? StripperMode { get; set; }
+    [JsonProperty("stripper_args")] public string? StripperArgs { get; set; }
+
+    // "anonymous" for depots Steam hands out without an account, anything else logs in
+    [JsonProperty("login")] public string? Login { get; set; }
+
+    [JsonIgnore] public bool IsAnonymous => Login?.ToLowerInvariant() == "anonymous";
+
+    // "oculus" for builds from the Oculus store, which are identified by a binary id instead of a manifest
+    [JsonProperty("store")] public string? Store { get; set; }
+    [JsonProperty("oculus_binary_id")] public string? OculusBinaryId { get; set; }
+
+    [JsonIgnore] public bool IsOculus => Store?.ToLowerInvariant() == "oculus";
+
+    private static readonly string[] Platforms = { "windows", "linux", "macos" };
+    private static readonly string[] Variants = { "linux", "macos", "oculus" };
+
+    // Game version first, so the builds of one version end up next to each other
+    public static readonly IComparer<string> KeyComparer = Comparer<string>.Create((a, b) =>
+    {
+        var (aVersion, aVariant) = SplitKey(a);
+        var (bVersion, bVariant) = SplitKey(b);
+        var result = SemVer.Parse(aVersion).CompareTo(SemVer.Parse(bVersion));
+        return result != 0 ? result : string.CompareOrdinal(aVariant, bVariant);
+    });
+
+    [JsonIgnore] public string DepotId => Depot ?? "620981";
+    [JsonIgnore] public string PlatformName => Platform?.ToLowerInvariant() ?? "windows";
+    [JsonIgnore] public string? Variant => VariantOf(Platform, Store);
+
+    // What a version is stored and branched under, other platforms and stores get a suffix so they can sit next to
+    // the Steam build for Windows of the same game version, e.g. versions/1.29.1_oculus on version/1.29.1_oculus.
+    // Underscores aren't semver syntax, so the suffix can't be mistaken for a prerelease like 1.29.1-beta
+    [JsonIgnore] public string Version => Key(GameVersion, Variant);
+
+    // Steam builds for Windows are the plain game version, everything else is kept apart under a variant
+    public static string? VariantOf(string? platform, string? store)
+    {
+        if (store?.ToLowerInvariant() == "oculus") return "oculus";
+
+        var name = platform?.ToLowerInvariant() ?? "windows";
+        return name == "windows" ? null : name;
+    }
+
+    public static string Key(string gameVersion, string? variant)
+    {
+        return variant == null ? gameVersion : $"{gameVersion}_{variant}";
+    }
+
+    public static (string GameVersion, string? Variant) SplitKey(string key)
+    {
+        foreach (var variant in Variants)
+            if (key.EndsWith($"_{variant}"))
+                return (key[..^(variant.Length + 1)], variant);
+
+        return (key, null);
+    }
+
+    // For versions that aren't in versions.json, everything but the manifest follows from the key
+    public static BeatSaberVersion FromKey(string key, string manifest)
+    {
+        var (gameVersion, variant) = SplitKey(key);
+        return new BeatSaberVersion
+        {
+            GameVersion = gameVersion,
+            Manifest = manifest,
+            Platform = variant is "linux" or "macos" ? variant : null,
+            Store = variant == "oculus" ? variant : null
+        };
+    }
+
+    public static async Task<List<BeatSaberVersion>?> LoadAll()
+    {
+        var versions =
+            JsonConvert.DeserializeObject<List<BeatSaberVersion>>(await File.ReadAllTextAsync("versions.json"));
+        foreach (var version in versions ?? new List<BeatSaberVersion>())
+        {
+            if (!Platforms.Contains(version.PlatformName))
+                throw new MbssException(ErrorKind.Config,
+                    $"Version {version.GameVersion} has unknown platform {version.Platform}, " +
+                    $"expected one of {string.Join(", ", Platforms)}!");
+            if (version.IsOculus && version.PlatformName != "windows")
+                throw new MbssException(ErrorKind.Config,
+                    $"Version {version.GameVersion} is an Oculus build, which only exists for Windows!");
+        }
+
+        return versions;
+    }
+
+    public static async Task SaveAll(List<BeatSaberVersion> versions)
+    {
+        await AtomicFile.WriteAllTextAsync("versions.json", JsonConvert.SerializeObject(versions, Formatting.Indented,
+            new JsonSerializerSettings { NullValueHandling = NullValueHandling.Ignore }));
+    }
+}
@@ -0,0 +1,63 @@
+﻿using Newtonsoft.Json;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal class CasStats
+{
+    [JsonProperty("ingested_files")] public long IngestedFiles { get; set; }
+    [JsonProperty("deduplicated_files")] public long DeduplicatedFiles { get; set; }
+    [JsonProperty("saved_bytes")] public long SavedBytes { get; set; }
+}
+
+// Stripped files are byte-identical across many versions, the store keeps one copy and links every checkout to it
+internal static class Cas
+{
+    public static bool Enabled => Environment.GetEnvironmentVariable("MBSS_CAS")?.ToLowerInvariant() is "1" or "true";
+
+    public static string Root => Path.GetFullPath(Environment.GetEnvironmentVariable("MBSS_CAS_DIR") ??
+                                                  Path.Combine("downloads", "cas"));
+
+    private static string StatsPath => Path.Combine(Root, "stats.json");
+
+    public static async Task Deduplicate(string directory)
+    {
+        var stats = await LoadStats();
+        // Links and executables are left alone, a shared inode can only have one mode
+        var files = await Checksums.Compute(directory, x => !FileCopy.IsSymlink(x) && !FileCopy.IsExecutable(x));
+        foreach (var (name, hash) in files)
+        {
+            var file = Path.Combine(directory, name);
+            var objectPath = Path.Combine(Root, hash[..2], hash[2..]);
+            if (File.Exists(objectPath))
+            {
+                var size = new FileInfo(file).Length;
+                if (FileCopy.Copy(objectPath, file) == "copy") continue;
+
+                stats.DeduplicatedFiles++;
+                stats.SavedBytes += size;
+                continue;
+            }
+
+            Directory.CreateDirectory(Path.GetDirectoryName(objectPath)!);
+            if (FileCopy.Copy(file, objectPath) == "copy")
+            {
+                // Without links the store would only double the disk usage
+                File.Delete(objectPath);
+                AnsiConsole.MarkupLine("[yellow]The blob cache needs hardlink or reflink support, skipping it.[/]");
+                break;
+            }
+
+            stats.IngestedFiles++;
+        }
+
+        await AtomicFile.WriteAllTextAsync(StatsPath, JsonConvert.SerializeObject(stats, Formatting.Indented));
+    }
+
+    public static async Task<CasStats> LoadStats()
+    {
+        return File.Exists(StatsPath)
+            ? JsonConvert.DeserializeObject<CasStats>(await File.ReadAllTextAsync(StatsPath)) ?? new CasStats()
+            : new CasStats();
+    }
+}
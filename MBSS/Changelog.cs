@@ -0,0 +1,72 @@
+﻿using System.Text;
+using LibGit2Sharp;
+
+namespace MBSS;
+
+internal static class Changelog
+{
+    public const string FileName = "CHANGELOG.md";
+    private const string Header = "# Changelog";
+
+    public static bool Enabled =>
+        Environment.GetEnvironmentVariable("MBSS_CHANGELOG")?.ToLowerInvariant() is "1" or "true";
+
+    public static async Task<bool> Update(BeatSaberVersion version)
+    {
+        var existing = File.Exists(FileName) ? await File.ReadAllTextAsync(FileName) : Header + "\n";
+        if (existing.Contains($"\n## {version.Version} ")) return false;
+
+        var versions = await BeatSaberVersion.LoadAll() ?? new List<BeatSaberVersion>();
+        var entry = new StringBuilder()
+            .AppendLine($"## {version.Version} (manifest {version.Manifest}, {DateTimeOffset.UtcNow:yyyy-MM-dd})")
+            .AppendLine();
+
+        using (var repo = new Repository(Workspace.RepositoryPath))
+        {
+            var tree = VersionTree.Find(repo, version.Version);
+            if (tree == null) return false;
+
+            if (VersionTree.FindPrevious(repo, versions, version.Version) is not { } previous)
+            {
+                var assemblies = VersionTree.Walk(tree).Count(x => IsAssembly(x.Path));
+                entry.AppendLine($"First mirrored version, {assemblies} assemblies.");
+            }
+            else
+            {
+                var diff = VersionDiff.Compute(repo, previous.Version, previous.Tree, version.Version, tree);
+                var added = diff.Added.Where(x => IsAssembly(x.Path)).ToList();
+                var removed = diff.Removed.Where(x => IsAssembly(x.Path)).ToList();
+                var changed = diff.Modified.Where(x => IsAssembly(x.Path)).ToList();
+
+                entry.AppendLine($"Compared to {previous.Version}:");
+                AppendSection(entry, "Added", added, x => x.NewAssemblyVersion ?? "-");
+                AppendSection(entry, "Removed", removed, x => x.OldAssemblyVersion ?? "-");
+                AppendSection(entry, "Changed", changed, x => x.OldAssemblyVersion == x.NewAssemblyVersion
+                    ? x.NewAssemblyVersion ?? "-"
+                    : $"{x.OldAssemblyVersion ?? "-"} → {x.NewAssemblyVersion ?? "-"}");
+                if (added.Count + removed.Count + changed.Count == 0)
+                    entry.AppendLine().AppendLine("No assembly changes.");
+            }
+        }
+
+        // Newest entries go first, right below the header
+        var body = existing.StartsWith(Header) ? existing[Header.Length..].TrimStart('\r', '\n') : existing;
+        await AtomicFile.WriteAllTextAsync(FileName,
+            $"{Header}\n\n{entry.ToString().TrimEnd()}\n\n{body}".TrimEnd() + "\n");
+        return true;
+    }
+
+    private static bool IsAssembly(string path)
+    {
+        return path.EndsWith(".dll", StringComparison.OrdinalIgnoreCase);
+    }
+
+    private static void AppendSection(StringBuilder entry, string title, List<DiffEntry> entries,
+        Func<DiffEntry, string> describe)
+    {
+        if (entries.Count == 0) return;
+
+        entry.AppendLine().AppendLine($"### {title}").AppendLine();
+        foreach (var diffEntry in entries) entry.AppendLine($"- `{diffEntry.Path}` ({describe(diffEntry)})");
+    }
+}
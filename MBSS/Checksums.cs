@@ -0,0 +1,42 @@
+﻿using System.Security.Cryptography;
+using System.Text;
+
+namespace MBSS;
+
+internal static class Checksums
+{
+    public const string FileName = "SHA256SUMS";
+
+    public static bool Enabled =>
+        Environment.GetEnvironmentVariable("MBSS_CHECKSUMS")?.ToLowerInvariant() is not ("0" or "false");
+
+    // Same format as sha256sum, so `sha256sum -c SHA256SUMS` works in any checkout of the version
+    public static async Task Write(string versionPath)
+    {
+        var output = Path.Combine(versionPath, FileName);
+        var sums = new StringBuilder();
+        foreach (var (name, hash) in await Compute(versionPath, x => x != output))
+            sums.Append($"{hash}  {name}\n");
+
+        await File.WriteAllTextAsync(output, sums.ToString());
+    }
+
+    public static async Task<List<(string Name, string Hash)>> Compute(string directory, Func<string, bool> include)
+    {
+        var files = Directory.EnumerateFiles(directory, "*", SearchOption.AllDirectories)
+            .Where(include)
+            .Select(x => (Path: x, Name: Path.GetRelativePath(directory, x).Replace('\\', '/')))
+            .OrderBy(x => x.Name, StringComparer.Ordinal)
+            .ToList();
+
+        var hashes = new (string Name, string Hash)[files.Count];
+        await Parallel.ForEachAsync(Enumerable.Range(0, files.Count), Parallelism.Options, async (i, token) =>
+        {
+            await using var stream = File.OpenRead(files[i].Path);
+            var hash = await SHA256.HashDataAsync(stream, token);
+            hashes[i] = (files[i].Name, Convert.ToHexString(hash).ToLowerInvariant());
+        });
+
+        return hashes.ToList();
+    }
+}
@@ -0,0 +1,51 @@
+﻿using Spectre.Console;
+
+namespace MBSS;
+
+// Output of the tools MBSS runs goes to a log per version instead of the console, unless --verbose is passed
+internal sealed class ChildLog : IDisposable
+{
+    private readonly string _name;
+    private readonly StreamWriter _writer;
+
+    private ChildLog(string name, string path)
+    {
+        _name = name;
+        Directory.CreateDirectory(System.IO.Path.GetDirectoryName(path)!);
+        _writer = new StreamWriter(path, true);
+        Path = path;
+        Write("mbss", $"Started {name}");
+    }
+
+    public static string Root =>
+        Environment.GetEnvironmentVariable("MBSS_CHILD_LOG_DIR") ?? System.IO.Path.Combine("downloads", "logs");
+
+    public string Path { get; }
+
+    public static ChildLog Open(string name)
+    {
+        // Tools run outside of a version, like the login check, share one log
+        var report = RunReport.Current;
+        var log = new ChildLog(name,
+            System.IO.Path.GetFullPath(System.IO.Path.Combine(Root, $"{report?.Version ?? "mbss"}.log")));
+        if (report != null) report.Log = log.Path;
+        return log;
+    }
+
+    public void Write(string stream, string line)
+    {
+        line = Secrets.Redact(line.TrimEnd('\r', '\n'));
+        lock (_writer)
+        {
+            _writer.WriteLine($"{DateTimeOffset.Now:yyyy-MM-dd HH:mm:ss} [{_name} {stream}] {line}");
+            _writer.Flush();
+        }
+
+        if (Options.Verbose) AnsiConsole.WriteLine(line);
+    }
+
+    public void Dispose()
+    {
+        _writer.Dispose();
+    }
+}
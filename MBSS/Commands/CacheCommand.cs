@@ -0,0 +1,38 @@
+﻿using Spectre.Console;
+
+namespace MBSS.Commands;
+
+internal static class CacheCommand
+{
+    public static async Task Run(string[] args)
+    {
+        switch (args.FirstOrDefault())
+        {
+            case "stats":
+                var stats = await Cas.LoadStats();
+                var objects = Directory.Exists(Cas.Root)
+                    ? Directory.EnumerateFiles(Cas.Root, "*", SearchOption.AllDirectories)
+                        .Count(x => Path.GetFileName(x) != "stats.json")
+                    : 0;
+
+                var table = new Table().AddColumns("Blob Cache", "Value");
+                table.AddRow("Location", Cas.Root.EscapeMarkup());
+                table.AddRow("Enabled", Cas.Enabled ? "yes" : "no (set MBSS_CAS=1)");
+                table.AddRow("Objects", objects.ToString());
+                table.AddRow("Size", Sizes.Format(Sizes.OfDirectory(Cas.Root)));
+                table.AddRow("Files ingested", stats.IngestedFiles.ToString());
+                table.AddRow("Files deduplicated", stats.DeduplicatedFiles.ToString());
+                table.AddRow("Space saved", Sizes.Format(stats.SavedBytes));
+                AnsiConsole.Write(table);
+                break;
+            case "clear":
+                // Checkouts keep their links, only the shared copy goes away
+                if (Directory.Exists(Cas.Root)) Directory.Delete(Cas.Root, true);
+                AnsiConsole.MarkupLine("[green]Cleared the blob cache.[/]");
+                break;
+            default:
+                AnsiConsole.MarkupLine("[red]Usage: MBSS cache <stats | clear>[/]");
+                break;
+        }
+    }
+}
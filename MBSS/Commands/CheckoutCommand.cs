@@ -0,0 +1,44 @@
+﻿using LibGit2Sharp;
+using Microsoft.Extensions.FileSystemGlobbing;
+using Spectre.Console;
+
+namespace MBSS.Commands;
+
+internal static class CheckoutCommand
+{
+    public static async Task<bool> Run(string[] args)
+    {
+        var version = args.FirstOrDefault();
+        var destination = Options.Value("--dest");
+        if (version == null || destination == null)
+        {
+            AnsiConsole.MarkupLine("[red]Usage: MBSS checkout <version> --dest <directory> [[--include <globs>]][/]");
+            return false;
+        }
+
+        using var repo = new Repository(Workspace.RepositoryPath);
+        var tree = VersionTree.Find(repo, version);
+        if (tree == null)
+        {
+            AnsiConsole.MarkupLine($"[red]Version {version.EscapeMarkup()} does not exist in the repository![/]");
+            return false;
+        }
+
+        // Blobs are read straight from the object database, the mirror's own checkout is left alone
+        var files = VersionTree.Walk(tree);
+        var include = Options.Value("--include");
+        if (!string.IsNullOrEmpty(include))
+        {
+            var matcher = new Matcher(StringComparison.OrdinalIgnoreCase);
+            matcher.AddIncludePatterns(include.Split(';', StringSplitOptions.RemoveEmptyEntries));
+            files = files.Where(x => matcher.Match(x.Path).HasMatches);
+        }
+
+        var selected = files.ToList();
+        await ExportCommand.ExportDirectory(selected, destination);
+        AnsiConsole.MarkupLine(
+            $"[green]Checked out {selected.Count} files of version {version.EscapeMarkup()} to " +
+            $"{destination.EscapeMarkup()}[/]");
+        return true;
+    }
+}
@@ -0,0 +1,129 @@
+﻿using System.Runtime.InteropServices;
+using MBSS.Notifiers;
+using Spectre.Console;
+
+namespace MBSS.Commands;
+
+internal static class DaemonCommand
+{
+    private static readonly TimeSpan DefaultInterval = TimeSpan.FromMinutes(30);
+
+    public static async Task Run(Func<string?, Task> runOnce)
+    {
+        var value = Options.Value("--interval") ?? Environment.GetEnvironmentVariable("MBSS_DAEMON_INTERVAL");
+        var interval = string.IsNullOrEmpty(value) ? DefaultInterval : Durations.Parse(value, "daemon interval");
+
+        using var shutdown = new CancellationTokenSource();
+
+        // Jobs left running by a crashed daemon are picked up again
+        using var triggers = new JobQueue();
+        triggers.RequeueInterrupted();
+        triggers.Watch();
+        var listen = Environment.GetEnvironmentVariable("MBSS_DAEMON_LISTEN");
+        if (!string.IsNullOrEmpty(listen))
+        {
+            var token = Environment.GetEnvironmentVariable("MBSS_DAEMON_TOKEN");
+            if (string.IsNullOrEmpty(token))
+                throw new MbssException(ErrorKind.Config, "MBSS_DAEMON_LISTEN requires MBSS_DAEMON_TOKEN!");
+
+            var server = new DaemonServer(listen, token, triggers);
+            server.Start();
+            _ = server.Run(shutdown.Token);
+        }
+
+        // The first signal lets the current run finish, the process exits before the next one starts. Cancelling
+        // the signal is what keeps the runtime from terminating right away, which ProcessExit can't do. A second
+        // one isn't cancelled, so a stuck run can still be killed with another Ctrl+C
+        var signals = new[] { PosixSignal.SIGTERM, PosixSignal.SIGINT, PosixSignal.SIGQUIT }
+            .Select(signal => PosixSignalRegistration.Create(signal, context =>
+            {
+                context.Cancel = !shutdown.IsCancellationRequested;
+                RequestShutdown(shutdown);
+            }))
+            .ToList();
+
+        var window = DownloadWindow.FromEnvironment();
+        var windowOpen = true;
+
+        AnsiConsole.MarkupLine($"[green]Running as a daemon, polling every {interval}[/]");
+        var nextScheduled = DateTimeOffset.UtcNow;
+        while (!shutdown.IsCancellationRequested)
+        {
+            // Scheduled runs go through the queue as well so they never race a triggered one
+            if (DateTimeOffset.UtcNow >= nextScheduled)
+            {
+                triggers.Enqueue(null, "schedule");
+
+                // Jitter keeps several mirrors from hitting Steam and GitHub at the same moment
+                nextScheduled = DateTimeOffset.UtcNow + interval * (0.9 + Random.Shared.NextDouble() * 0.2);
+                AnsiConsole.MarkupLine($"[yellow]Next scheduled run at {nextScheduled.ToLocalTime():t}[/]");
+            }
+
+            // Jobs keep queueing up outside the download window, they're only run once it opens
+            var job = window == null || window.IsOpen(DateTime.Now) ? triggers.NextDue() : null;
+            if (window != null && windowOpen != window.IsOpen(DateTime.Now))
+            {
+                windowOpen = !windowOpen;
+                var opens = window.NextOpen(DateTime.Now);
+                AnsiConsole.MarkupLine(windowOpen
+                    ? $"[green]Download window {window} opened[/]"
+                    : $"[yellow]Outside the download window {window}, holding jobs until {opens:t}[/]");
+            }
+
+            if (job != null)
+            {
+                await RunJob(triggers, job, runOnce);
+                continue;
+            }
+
+            var wakeUp = triggers.NextWakeUp() is { } retry && retry < nextScheduled ? retry : nextScheduled;
+            if (window != null && !windowOpen)
+            {
+                var opens = new DateTimeOffset(window.NextOpen(DateTime.Now));
+                wakeUp = opens < nextScheduled ? opens : nextScheduled;
+            }
+
+            try
+            {
+                var delay = wakeUp - DateTimeOffset.UtcNow;
+                await triggers.WaitAsync(delay > TimeSpan.Zero ? delay : TimeSpan.Zero, shutdown.Token);
+            }
+            catch (OperationCanceledException)
+            {
+                break;
+            }
+        }
+
+        foreach (var signal in signals) signal.Dispose();
+        AnsiConsole.MarkupLine("[green]Daemon stopped.[/]");
+    }
+
+    private static async Task RunJob(JobQueue triggers, Job job, Func<string?, Task> runOnce)
+    {
+        AnsiConsole.MarkupLine(
+            $"[yellow]Running job {job.Id} for {(job.Version ?? "all versions").EscapeMarkup()} ({job.Source})[/]");
+        try
+        {
+            await runOnce(job.Version);
+            if (RunReport.Failed)
+                triggers.Fail(job, $"{RunReport.Versions.Count(x => x.Status == "failed")} versions failed");
+            else
+                triggers.Complete(job);
+        }
+        catch (Exception e)
+        {
+            AnsiConsole.MarkupLine($"[red]Run failed: {e.Message.EscapeMarkup()}[/]");
+            triggers.Fail(job, e.Message, MbssException.IsRetryable(e));
+            await ErrorReporter.Report(e);
+            await Notifications.RunFailed(e);
+        }
+    }
+
+    private static void RequestShutdown(CancellationTokenSource shutdown)
+    {
+        if (shutdown.IsCancellationRequested) return;
+
+        AnsiConsole.MarkupLine("[yellow]Shutting down after the current run...[/]");
+        shutdown.Cancel();
+    }
+}
@@ -0,0 +1,169 @@
+﻿using System.Net;
+using System.Security.Cryptography;
+using System.Text;
+using LibGit2Sharp;
+using Newtonsoft.Json;
+using Spectre.Console;
+
+namespace MBSS.Commands;
+
+internal class DaemonServer
+{
+    // GitHub caps webhook payloads at 25 MB, but the events that trigger a run are a fraction of that
+    private const int MaxBodySize = 1024 * 1024;
+
+    private readonly HttpListener _listener = new();
+    private readonly string _prefix;
+    private readonly string _token;
+    private readonly JobQueue _triggers;
+
+    public DaemonServer(string prefix, string token, JobQueue triggers)
+    {
+        _prefix = prefix.EndsWith('/') ? prefix : $"{prefix}/";
+        _token = token;
+        _triggers = triggers;
+    }
+
+    // Started before the daemon goes on, a port that's taken would otherwise leave it running without its API
+    public void Start()
+    {
+        try
+        {
+            _listener.Prefixes.Add(_prefix);
+            _listener.Start();
+        }
+        catch (Exception e) when (e is HttpListenerException or ArgumentException)
+        {
+            throw new MbssException(ErrorKind.Config, $"Couldn't listen on {_prefix}: {e.Message}!", e);
+        }
+
+        AnsiConsole.MarkupLine($"[green]Listening on {_prefix}[/]");
+    }
+
+    public async Task Run(CancellationToken token)
+    {
+        try
+        {
+            while (!token.IsCancellationRequested)
+            {
+                var context = await _listener.GetContextAsync().WaitAsync(token);
+                _ = Task.Run(() => Handle(context), token);
+            }
+        }
+        catch (OperationCanceledException)
+        {
+        }
+        finally
+        {
+            _listener.Stop();
+        }
+    }
+
+    private async Task Handle(HttpListenerContext context)
+    {
+        var req = context.Request;
+        var res = context.Response;
+        var path = req.Url?.AbsolutePath.TrimEnd('/') ?? string.Empty;
+
+        try
+        {
+            if (req.HttpMethod == "GET" && path == "/metrics")
+            {
+                await Respond(res, HttpStatusCode.OK, Metrics.Render(), "text/plain; version=0.0.4");
+                return;
+            }
+
+            if (!await IsAuthorized(req))
+            {
+                await Respond(res, HttpStatusCode.Unauthorized, "Unauthorized");
+                return;
+            }
+
+            if (req.HttpMethod == "POST" && (path == "/run" || path.StartsWith("/run/")))
+            {
+                var version = path == "/run" ? null : Uri.UnescapeDataString(path["/run/".Length..]);
+                var job = _triggers.Enqueue(version, "http");
+                AnsiConsole.MarkupLine($"[yellow]Run of {(version ?? "all versions").EscapeMarkup()} triggered[/]");
+                await Respond(res, HttpStatusCode.Accepted, job == null ? "Already queued" : $"Queued {job.Id}");
+                return;
+            }
+
+            if (req.HttpMethod == "GET" && path == "/status")
+            {
+                await RespondJson(res, Status());
+                return;
+            }
+
+            if (req.HttpMethod == "GET" && path == "/versions")
+            {
+                using var repo = new Repository(Workspace.RepositoryPath);
+                await RespondJson(res, VersionTree.CommitIndex(repo));
+                return;
+            }
+
+            await Respond(res, HttpStatusCode.NotFound, "Not found");
+        }
+        catch (Exception e)
+        {
+            AnsiConsole.MarkupLine($"[red]Failed to handle {req.HttpMethod} {path}: {e.Message.EscapeMarkup()}[/]");
+            res.Abort();
+        }
+    }
+
+    private object Status()
+    {
+        var run = RunReport.Snapshot();
+        return new
+        {
+            last_run = new { started_at = run.StartedAt, finished_at = run.FinishedAt, versions = run.Versions },
+            processing = run.Current == null
+                ? null
+                : new { version = run.Current, stage = run.Stage, seconds = run.Seconds },
+            queue = _triggers.List()
+        };
+    }
+
+    private async Task<bool> IsAuthorized(HttpListenerRequest req)
+    {
+        // Ops tooling sends the token directly, GitHub webhooks sign the payload with it instead
+        var auth = req.Headers["Authorization"];
+        if (auth != null && auth.StartsWith("Bearer ")) return FixedTimeEquals(auth["Bearer ".Length..], _token);
+
+        var signature = req.Headers["X-Hub-Signature-256"];
+        if (signature == null || !signature.StartsWith("sha256=")) return false;
+
+        // Only signed requests are read, and never past the cap, anything longer can't be a trigger
+        if (req.ContentLength64 > MaxBodySize) return false;
+        var body = new byte[MaxBodySize + 1];
+        var length = 0;
+        int read;
+        while (length < body.Length && (read = await req.InputStream.ReadAsync(body.AsMemory(length))) > 0)
+            length += read;
+        if (length > MaxBodySize) return false;
+
+        var expected = HMACSHA256.HashData(Encoding.UTF8.GetBytes(_token), body.AsSpan(0, length));
+        return FixedTimeEquals(signature["sha256=".Length..], Convert.ToHexString(expected).ToLowerInvariant());
+    }
+
+    private static bool FixedTimeEquals(string a, string b)
+    {
+        return CryptographicOperations.FixedTimeEquals(Encoding.UTF8.GetBytes(a), Encoding.UTF8.GetBytes(b));
+    }
+
+    private static Task RespondJson(HttpListenerResponse res, object body)
+    {
+        return Respond(res, HttpStatusCode.OK, JsonConvert.SerializeObject(body, Formatting.Indented),
+            "application/json");
+    }
+
+    private static async Task Respond(HttpListenerResponse res, HttpStatusCode status, string body,
+        string contentType = "text/plain")
+    {
+        var bytes = Encoding.UTF8.GetBytes(body);
+        res.StatusCode = (int)status;
+        res.ContentType = contentType;
+        res.ContentLength64 = bytes.Length;
+        await res.OutputStream.WriteAsync(bytes);
+        res.Close();
+    }
+}
@@ -0,0 +1,57 @@
+﻿using LibGit2Sharp;
+using Newtonsoft.Json;
+using Spectre.Console;
+
+namespace MBSS.Commands;
+
+internal static class DiffCommand
+{
+    public static void Run(string[] args)
+    {
+        if (args.Length != 2)
+        {
+            AnsiConsole.MarkupLine("[red]Usage: MBSS diff <from> <to> [[--format json]][/]");
+            return;
+        }
+
+        using var repo = new Repository(Workspace.RepositoryPath);
+        var fromTree = VersionTree.Find(repo, args[0]);
+        var toTree = VersionTree.Find(repo, args[1]);
+        if (fromTree == null || toTree == null)
+        {
+            var missing = fromTree == null ? args[0] : args[1];
+            AnsiConsole.MarkupLine($"[red]Version {missing.EscapeMarkup()} does not exist in the repository![/]");
+            return;
+        }
+
+        var diff = VersionDiff.Compute(repo, args[0], fromTree, args[1], toTree);
+        if (Options.Value("--format") == "json")
+        {
+            Console.WriteLine(JsonConvert.SerializeObject(diff, Formatting.Indented));
+            return;
+        }
+
+        var table = new Table().AddColumns("Change", "Path", "Size", "Assembly Version");
+        foreach (var entry in diff.Added) table.AddRow(Describe("[green]added[/]", entry));
+        foreach (var entry in diff.Removed) table.AddRow(Describe("[red]removed[/]", entry));
+        foreach (var entry in diff.Modified) table.AddRow(Describe("[yellow]modified[/]", entry));
+
+        AnsiConsole.Write(table);
+        AnsiConsole.MarkupLine(
+            $"{diff.Added.Count} added, {diff.Removed.Count} removed, {diff.Modified.Count} modified, " +
+            $"{Sizes.Format(diff.SizeDelta, true)} total");
+    }
+
+    private static string[] Describe(string change, DiffEntry entry)
+    {
+        var size = entry.OldSize != null && entry.NewSize != null
+            ? $"{Sizes.Format(entry.NewSize.Value)} ({Sizes.Format(entry.NewSize.Value - entry.OldSize.Value, true)})"
+            : Sizes.Format(entry.NewSize ?? entry.OldSize ?? 0);
+
+        var assemblyVersion = entry.OldAssemblyVersion == entry.NewAssemblyVersion
+            ? entry.NewAssemblyVersion ?? string.Empty
+            : $"{entry.OldAssemblyVersion ?? "-"} -> {entry.NewAssemblyVersion ?? "-"}";
+
+        return new[] { change, entry.Path.EscapeMarkup(), size, assemblyVersion.EscapeMarkup() };
+    }
+}
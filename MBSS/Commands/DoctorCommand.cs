@@ -0,0 +1,121 @@
+﻿using System.Diagnostics;
+using LibGit2Sharp;
+using MBSS.Tools;
+using Spectre.Console;
+
+namespace MBSS.Commands;
+
+internal static class DoctorCommand
+{
+    public static async Task<bool> Run(HttpClient client)
+    {
+        var depotSource = Program.CreateDepotSource();
+        var checks = new List<(string Name, Func<Task<string?>> Check)>
+        {
+            ("versions.json", CheckVersions),
+            ("Git repository", () => Task.FromResult(CheckRepository())),
+            ("Credentials", () => Task.FromResult(CheckCredentials(depotSource.RequiresSteam))),
+            ("GenericStripper", () => CheckTool(GitHubTool.GenericStripper)),
+            ("Disk space", () => Wrap(() => DiskSpace.CheckMargin(Directory.GetCurrentDirectory()))),
+            ("Remote access", () => WrapAsync(() => Preflight.CheckRemote(client)))
+        };
+
+        if (depotSource.RequiresSteam)
+        {
+            if (depotSource.RequiresDepotDownloader)
+                checks.Add(("DepotDownloader", () => CheckTool(GitHubTool.DepotDownloader)));
+            checks.Add(("Steam login", () => WrapAsync(depotSource.Preflight)));
+        }
+        else
+        {
+            checks.Add(("Depot source", () => WrapAsync(depotSource.Preflight)));
+        }
+
+        var table = new Table().AddColumns("Check", "Status", "Fix");
+        var healthy = true;
+        foreach (var (name, check) in checks)
+        {
+            string? fix;
+            try
+            {
+                fix = await check();
+            }
+            catch (Exception e)
+            {
+                fix = e.Message;
+            }
+
+            healthy &= fix == null;
+            table.AddRow(name, fix == null ? "[green]OK[/]" : "[red]FAIL[/]", (fix ?? string.Empty).EscapeMarkup());
+        }
+
+        AnsiConsole.MarkupLine($"Running on .NET {Environment.Version} ({Environment.OSVersion})");
+        AnsiConsole.Write(table);
+        return healthy;
+    }
+
+    private static async Task<string?> CheckVersions()
+    {
+        if (!File.Exists("versions.json"))
+            return Environment.GetEnvironmentVariable("MBSS_VERSIONS_SOURCE_URL") == null
+                ? "Create versions.json or set MBSS_VERSIONS_SOURCE_URL"
+                : null;
+
+        var versions = await BeatSaberVersion.LoadAll();
+        if (versions == null) return "versions.json is empty or not a JSON array";
+
+        var invalid = versions.FirstOrDefault(x => string.IsNullOrEmpty(x.Version) || string.IsNullOrEmpty(x.Manifest));
+        return invalid == null ? null : "Every entry in versions.json needs a version and a manifest";
+    }
+
+    private static string? CheckRepository()
+    {
+        if (!Repository.IsValid(Workspace.RepositoryPath)) return "Run MBSS from the root of a Git repository";
+
+        using var repo = new Repository(Workspace.RepositoryPath);
+        return repo.RetrieveStatus().IsDirty ? "Commit or discard local changes before running MBSS" : null;
+    }
+
+    private static string? CheckCredentials(bool requiresSteam)
+    {
+        var envs = new List<string> { "GIT_AUTHOR_NAME", "GIT_AUTHOR_EMAIL", "GITHUB_TOKEN" };
+        if (requiresSteam) envs.AddRange(SteamAccounts.CredentialEnvs);
+        Credentials.Load(envs);
+
+        var missing = envs.Where(env => string.IsNullOrEmpty(Environment.GetEnvironmentVariable(env))).ToList();
+        return missing.Count == 0 ? null : $"Set {string.Join(", ", missing)} in the environment or .env";
+    }
+
+    private static async Task<string?> CheckTool(GitHubTool tool)
+    {
+        if (!tool.IsInstalled)
+            return tool.PathOverride != null
+                ? $"Fix MBSS_{tool.Name.ToUpperInvariant()}_PATH, {tool.PathOverride} does not exist"
+                : "Run MBSS tools upgrade to install it";
+
+        using var process = Process.Start(new ProcessStartInfo(tool.ExecutablePath, "--help")
+        {
+            RedirectStandardOutput = true,
+            RedirectStandardError = true,
+            UseShellExecute = false
+        });
+        if (process == null) return $"{tool.ExecutablePath} could not be started";
+
+        var exited = process.WaitForExitAsync();
+        if (await Task.WhenAny(exited, Task.Delay(TimeSpan.FromSeconds(15))) != exited) process.Kill(true);
+
+        return null;
+    }
+
+    private static Task<string?> Wrap(Action check)
+    {
+        check();
+        return Task.FromResult<string?>(null);
+    }
+
+    private static async Task<string?> WrapAsync(Func<Task> check)
+    {
+        await check();
+        return null;
+    }
+}
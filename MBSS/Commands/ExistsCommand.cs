@@ -0,0 +1,57 @@
+﻿using LibGit2Sharp;
+using Spectre.Console;
+
+namespace MBSS.Commands;
+
+internal static class ExistsCommand
+{
+    public static bool Run(string[] args)
+    {
+        if (args.Length != 1)
+        {
+            AnsiConsole.MarkupLine(
+                "[red]Usage: MBSS exists <version> [[--platform <platform>]] [[--store oculus]] [[--remote]][/]");
+            return false;
+        }
+
+        var version = BeatSaberVersion.Key(args[0],
+            BeatSaberVersion.VariantOf(Options.Value("--platform"), Options.Value("--store")));
+
+        using var repo = new Repository(Workspace.RepositoryPath);
+        var exists = Options.Has("--remote") ? ExistsOnRemote(repo, version) : ExistsLocally(repo, version);
+
+        // The exit code is the answer, this is only for humans reading along
+        Console.Error.WriteLine(exists ? $"{version} is mirrored" : $"{version} is not mirrored");
+        return exists;
+    }
+
+    private static bool ExistsLocally(Repository repo, string version)
+    {
+        return VersionTree.Find(repo, version) != null || OrphanBranches.Exists(repo, version);
+    }
+
+    private static bool ExistsOnRemote(Repository repo, string version)
+    {
+        var remote = repo.Network.Remotes["origin"] ??
+            throw new MbssException(ErrorKind.Config, "No origin remote configured!");
+
+        // Exit 1 means not mirrored, an unreachable remote must not be mistaken for that answer
+        try
+        {
+            var branch = $"refs/heads/{OrphanBranches.BranchName(version)}";
+            if (Repository.ListRemoteReferences(remote.Url, Credentials.GitHandler)
+                .Any(x => x.CanonicalName == branch))
+                return true;
+
+            // Without an orphan branch the version can only be a directory on the remote's main
+            Commands.Fetch(repo, remote.Name, new[] { "+refs/heads/main:refs/remotes/origin/main" },
+                Credentials.GitFetchOptions, null);
+        }
+        catch (LibGit2SharpException e)
+        {
+            throw new MbssException(ErrorKind.Config, $"Couldn't reach origin: {e.Message}!", e);
+        }
+
+        return repo.Branches["origin/main"]?.Tip[$"versions/{version}"]?.Target is Tree;
+    }
+}
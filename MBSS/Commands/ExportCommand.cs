@@ -0,0 +1,78 @@
+﻿using System.Formats.Tar;
+using System.IO.Compression;
+using LibGit2Sharp;
+using Spectre.Console;
+
+namespace MBSS.Commands;
+
+internal static class ExportCommand
+{
+    public static async Task Run(string[] args)
+    {
+        var version = args.FirstOrDefault();
+        var output = Options.Value("--out");
+        if (version == null || output == null)
+        {
+            AnsiConsole.MarkupLine("[red]Usage: MBSS export <version> --out <directory|.zip|.tar|.tar.gz>[/]");
+            return;
+        }
+
+        using var repo = new Repository(Workspace.RepositoryPath);
+        var tree = VersionTree.Find(repo, version);
+        if (tree == null)
+        {
+            AnsiConsole.MarkupLine($"[red]Version {version.EscapeMarkup()} does not exist in the repository![/]");
+            return;
+        }
+
+        var files = VersionTree.Walk(tree).ToList();
+        if (output.EndsWith(".zip", StringComparison.OrdinalIgnoreCase))
+            await ExportZip(files, output);
+        else if (output.EndsWith(".tar", StringComparison.OrdinalIgnoreCase))
+            await ExportTar(files, output, false);
+        else if (output.EndsWith(".tar.gz", StringComparison.OrdinalIgnoreCase) ||
+                 output.EndsWith(".tgz", StringComparison.OrdinalIgnoreCase))
+            await ExportTar(files, output, true);
+        else
+            await ExportDirectory(files, output);
+
+        AnsiConsole.MarkupLine($"[green]Exported {files.Count} files of version {version} to {output}![/]");
+    }
+
+    public static async Task ExportDirectory(IEnumerable<(string Path, TreeEntry Entry)> files, string output)
+    {
+        foreach (var (path, entry) in files)
+        {
+            var target = Path.Combine(output, path);
+            Directory.CreateDirectory(Path.GetDirectoryName(target)!);
+
+            await using var source = ((Blob)entry.Target).GetContentStream();
+            await using var file = File.Create(target);
+            await source.CopyToAsync(file);
+        }
+    }
+
+    private static async Task ExportZip(IEnumerable<(string Path, TreeEntry Entry)> files, string output)
+    {
+        await using var file = File.Create(output);
+        using var archive = new ZipArchive(file, ZipArchiveMode.Create);
+        foreach (var (path, entry) in files)
+        {
+            await using var source = ((Blob)entry.Target).GetContentStream();
+            await using var target = archive.CreateEntry(path, CompressionLevel.Optimal).Open();
+            await source.CopyToAsync(target);
+        }
+    }
+
+    private static async Task ExportTar(IEnumerable<(string Path, TreeEntry Entry)> files, string output, bool gzip)
+    {
+        await using var file = File.Create(output);
+        await using Stream stream = gzip ? new GZipStream(file, CompressionLevel.Optimal) : file;
+        await using var writer = new TarWriter(stream);
+        foreach (var (path, entry) in files)
+        {
+            await using var source = ((Blob)entry.Target).GetContentStream();
+            await writer.WriteEntryAsync(new PaxTarEntry(TarEntryType.RegularFile, path) { DataStream = source });
+        }
+    }
+}
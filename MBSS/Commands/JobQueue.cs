@@ -0,0 +1,157 @@
+﻿using Newtonsoft.Json;
+
+namespace MBSS.Commands;
+
+internal class Job
+{
+    [JsonProperty("id")] public string Id { get; set; } = Guid.NewGuid().ToString("N")[..8];
+    [JsonProperty("version")] public string? Version { get; set; }
+    [JsonProperty("source")] public string Source { get; set; } = string.Empty;
+    [JsonProperty("status")] public string Status { get; set; } = "queued";
+    [JsonProperty("attempts")] public int Attempts { get; set; }
+    [JsonProperty("created_at")] public DateTimeOffset CreatedAt { get; set; } = DateTimeOffset.UtcNow;
+    [JsonProperty("not_before")] public DateTimeOffset NotBefore { get; set; } = DateTimeOffset.UtcNow;
+    [JsonProperty("error")] public string? Error { get; set; }
+}
+
+internal class JobQueue : IDisposable
+{
+    private readonly string _path;
+    private readonly SemaphoreSlim _signal = new(0);
+    private FileSystemWatcher? _watcher;
+
+    public JobQueue()
+    {
+        _path = Path.GetFullPath(Environment.GetEnvironmentVariable("MBSS_QUEUE_PATH") ?? ".mbss-queue.json");
+    }
+
+    private static int MaxAttempts =>
+        int.TryParse(Environment.GetEnvironmentVariable("MBSS_JOB_MAX_ATTEMPTS"), out var attempts) ? attempts : 3;
+
+    public Job? Enqueue(string? version, string source)
+    {
+        var job = Update(jobs =>
+        {
+            // A queued job for the same version (or a full run) already covers this one
+            if (jobs.Any(x => x.Status == "queued" && (x.Version == version || x.Version == null))) return null;
+
+            var job = new Job { Version = version, Source = source };
+            jobs.Add(job);
+            return job;
+        });
+
+        if (job != null) _signal.Release();
+        return job;
+    }
+
+    public Job? NextDue()
+    {
+        return Update(jobs =>
+        {
+            var job = jobs
+                .Where(x => x.Status == "queued" && x.NotBefore <= DateTimeOffset.UtcNow)
+                .MinBy(x => x.CreatedAt);
+            if (job != null) job.Status = "running";
+            return job;
+        });
+    }
+
+    public void RequeueInterrupted()
+    {
+        Update(jobs =>
+        {
+            foreach (var job in jobs.Where(x => x.Status == "running")) job.Status = "queued";
+            return 0;
+        });
+    }
+
+    public DateTimeOffset? NextWakeUp()
+    {
+        return Update(jobs => jobs.Where(x => x.Status == "queued").Select(x => (DateTimeOffset?)x.NotBefore).Min());
+    }
+
+    public void Complete(Job job)
+    {
+        Update(jobs => jobs.RemoveAll(x => x.Id == job.Id));
+    }
+
+    public void Fail(Job job, string error, bool retry = true)
+    {
+        Update(jobs =>
+        {
+            var stored = jobs.FirstOrDefault(x => x.Id == job.Id);
+            if (stored == null) return 0; // Cancelled while it was running
+
+            // Retries back off exponentially, jobs that keep failing are kept around for inspection
+            stored.Attempts++;
+            stored.Error = error;
+            stored.Status = !retry || stored.Attempts >= MaxAttempts ? "failed" : "queued";
+            stored.NotBefore = DateTimeOffset.UtcNow + TimeSpan.FromMinutes(5 * Math.Pow(2, stored.Attempts - 1));
+            return 0;
+        });
+    }
+
+    public int Cancel(string idOrVersion)
+    {
+        return Update(jobs => jobs.RemoveAll(x => x.Id == idOrVersion || x.Version == idOrVersion ||
+                                                  (idOrVersion == "*" && x.Version == null)));
+    }
+
+    public List<Job> List()
+    {
+        return Update(jobs => jobs.ToList());
+    }
+
+    public async Task WaitAsync(TimeSpan timeout, CancellationToken token)
+    {
+        await _signal.WaitAsync(timeout, token);
+    }
+
+    // Jobs added with MBSS queue only reach the daemon through the file, so changes to it wake the daemon up as well
+    public void Watch()
+    {
+        _watcher = new FileSystemWatcher(Path.GetDirectoryName(_path)!, Path.GetFileName(_path))
+        {
+            NotifyFilter = NotifyFilters.LastWrite | NotifyFilters.Size | NotifyFilters.FileName
+        };
+        _watcher.Changed += (_, _) => _signal.Release();
+        _watcher.Created += (_, _) => _signal.Release();
+        _watcher.EnableRaisingEvents = true;
+    }
+
+    public void Dispose()
+    {
+        _watcher?.Dispose();
+        _signal.Dispose();
+    }
+
+    private T Update<T>(Func<List<Job>, T> action)
+    {
+        // The daemon and the queue command can touch the file at the same time, an exclusive handle serializes them
+        for (var attempt = 0;; attempt++)
+        {
+            try
+            {
+                using var stream = new FileStream(_path, FileMode.OpenOrCreate, FileAccess.ReadWrite, FileShare.None);
+                using var reader = new StreamReader(stream, leaveOpen: true);
+                var original = reader.ReadToEnd();
+                var jobs = JsonConvert.DeserializeObject<List<Job>>(original) ?? new List<Job>();
+
+                var result = action(jobs);
+
+                // Unchanged queues aren't written back, the watcher would wake the daemon up for its own reads
+                var updated = JsonConvert.SerializeObject(jobs, Formatting.Indented);
+                if (updated == original) return result;
+
+                stream.SetLength(0);
+                using var writer = new StreamWriter(stream);
+                writer.Write(updated);
+                return result;
+            }
+            catch (IOException) when (attempt < 50)
+            {
+                Thread.Sleep(100);
+            }
+        }
+    }
+}
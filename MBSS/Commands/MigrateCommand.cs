@@ -0,0 +1,89 @@
+﻿using System.Text.RegularExpressions;
+using LibGit2Sharp;
+using Spectre.Console;
+
+namespace MBSS.Commands;
+
+internal static class MigrateCommand
+{
+    public static void Run()
+    {
+        var fromPattern = Options.Value("--from-pattern");
+        var toPattern = Options.Value("--to-pattern");
+        if (fromPattern == null || !fromPattern.Contains("{version}"))
+        {
+            AnsiConsole.MarkupLine(
+                "[red]Usage: MBSS migrate --from-pattern <pattern with {version}> [[--to-pattern <pattern>]] " +
+                "[[--dry-run]][/]");
+            return;
+        }
+
+        var regex = new Regex("^" + Regex.Escape(fromPattern).Replace(@"\{version}", "(?<version>.+)") + "$");
+
+        using var repo = new Repository(Workspace.RepositoryPath);
+        var remote = Options.Offline ? null : repo.Network.Remotes["origin"];
+
+        // Old branches may only exist on the remote, those are fetched so they can be recreated under the new name
+        var branches = repo.Branches.Where(x => !x.IsRemote)
+            .ToDictionary(x => x.FriendlyName, x => x.Tip.Sha);
+        var remoteBranches = new HashSet<string>();
+        if (remote != null)
+        {
+            foreach (var reference in Repository.ListRemoteReferences(remote.Url, Credentials.GitHandler)
+                         .Where(x => x.CanonicalName.StartsWith("refs/heads/")))
+            {
+                var name = reference.CanonicalName["refs/heads/".Length..];
+                remoteBranches.Add(name);
+                if (!branches.ContainsKey(name) && regex.IsMatch(name))
+                    branches[name] = reference.TargetIdentifier;
+            }
+        }
+
+        var renames = branches
+            .Select(x => (Old: x.Key, Sha: x.Value, Match: regex.Match(x.Key)))
+            .Where(x => x.Match.Success)
+            .Select(x => (x.Old, New: OrphanBranches.BranchName(x.Match.Groups["version"].Value, toPattern), x.Sha))
+            .Where(x => x.Old != x.New)
+            .ToList();
+
+        if (renames.Count == 0)
+        {
+            AnsiConsole.MarkupLine($"[yellow]No branches match {fromPattern.EscapeMarkup()}.[/]");
+            return;
+        }
+
+        var table = new Table().AddColumns("From", "To", "Commit");
+        foreach (var (oldName, newName, sha) in renames)
+            table.AddRow(oldName.EscapeMarkup(), newName.EscapeMarkup(), sha[..7]);
+        AnsiConsole.Write(table);
+        if (Options.DryRun) return;
+
+        var missing = renames
+            .Where(x => repo.Lookup<Commit>(x.Sha) == null)
+            .Select(x => $"refs/heads/{x.Old}")
+            .ToList();
+        if (remote != null && missing.Count > 0)
+            Commands.Fetch(repo, remote.Name, missing.Select(x => $"+{x}:{x}"),
+                new FetchOptions { CredentialsProvider = Credentials.GitHandler }, null);
+
+        var pushOptions = new PushOptions { CredentialsProvider = Credentials.GitHandler };
+        foreach (var (oldName, newName, sha) in renames)
+        {
+            if (repo.Branches[newName] is { } existing && existing.Tip.Sha != sha)
+                throw new MbssException(ErrorKind.Repository,
+                    $"{newName} already exists and points at a different commit than {oldName}!");
+
+            repo.Refs.Add($"refs/heads/{newName}", new ObjectId(sha), true);
+
+            // The new name is pushed before the old one is deleted, so a failure never leaves the commit unreachable
+            if (remote != null)
+            {
+                repo.Network.Push(remote, $"refs/heads/{newName}:refs/heads/{newName}", pushOptions);
+                if (remoteBranches.Contains(oldName)) repo.Network.Push(remote, $":refs/heads/{oldName}", pushOptions);
+            }
+
+            if (repo.Branches[oldName] != null) repo.Branches.Remove(oldName);
+            AnsiConsole.MarkupLine($"[green]Renamed {oldName.EscapeMarkup()} to {newName.EscapeMarkup()}[/]");
+        }
+    }
+}
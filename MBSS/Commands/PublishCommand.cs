@@ -0,0 +1,47 @@
+﻿using LibGit2Sharp;
+using MBSS.Publishing;
+using Spectre.Console;
+
+namespace MBSS.Commands;
+
+internal static class PublishCommand
+{
+    public static async Task Run(string[] args)
+    {
+        var versionName = args.FirstOrDefault();
+        if (versionName == null)
+        {
+            AnsiConsole.MarkupLine("[red]Usage: MBSS publish <version>[/]");
+            return;
+        }
+
+        var versions = await BeatSaberVersion.LoadAll();
+        var version = versions?.FirstOrDefault(x => x.Version == versionName) ??
+                      BeatSaberVersion.FromKey(versionName, "unknown");
+
+        // Publishers work on files, so the committed tree is exported rather than trusting the checkout
+        var exportPath = Path.GetFullPath(Path.Combine("downloads", $"{version.Version}.publish"));
+        if (Directory.Exists(exportPath)) Directory.Delete(exportPath, true);
+        try
+        {
+            using (var repo = new Repository(Workspace.RepositoryPath))
+            {
+                var tree = VersionTree.Find(repo, version.Version);
+                if (tree == null)
+                {
+                    AnsiConsole.MarkupLine(
+                        $"[red]Version {version.Version.EscapeMarkup()} does not exist in the repository![/]");
+                    return;
+                }
+
+                await ExportCommand.ExportDirectory(VersionTree.Walk(tree), exportPath);
+            }
+
+            await Publishers.Publish(version, exportPath);
+        }
+        finally
+        {
+            if (Directory.Exists(exportPath)) Directory.Delete(exportPath, true);
+        }
+    }
+}
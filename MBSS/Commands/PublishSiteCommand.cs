@@ -0,0 +1,146 @@
+﻿using System.Net;
+using System.Text;
+using LibGit2Sharp;
+using MBSS.Publishing;
+using Newtonsoft.Json;
+using Spectre.Console;
+
+namespace MBSS.Commands;
+
+internal static class PublishSiteCommand
+{
+    public static async Task Run()
+    {
+        var versions = File.Exists("versions.json") ? await BeatSaberVersion.LoadAll() : null;
+        versions ??= new List<BeatSaberVersion>();
+
+        var files = new Dictionary<string, string>();
+        using var repo = new Repository(Workspace.RepositoryPath);
+        var remoteUrl = VersionFiles.PublicRemote();
+        var releases = Publishers.FromEnvironment().OfType<GitHubReleasePublisher>().FirstOrDefault();
+        var index = VersionTree.CommitIndex(repo);
+
+        var catalog = new List<object>();
+        var rows = new StringBuilder();
+        foreach (var version in versions)
+        {
+            var tree = VersionTree.Find(repo, version.Version);
+            if (tree == null) continue;
+
+            var blobs = VersionTree.Walk(tree).Select(x => (x.Path, Blob: (Blob)x.Entry.Target)).ToList();
+            var size = blobs.Sum(x => x.Blob.Size);
+            var diff = VersionTree.FindPrevious(repo, versions, version.Version) is { } previous
+                ? VersionDiff.Compute(repo, previous.Version, previous.Tree, version.Version, tree)
+                : null;
+            var refs = index.GetValueOrDefault(version.Version);
+            var url = remoteUrl == null ? null : VersionTree.WebUrl(remoteUrl, version.Version);
+            var download = remoteUrl == null ? null : releases?.DownloadUrl(remoteUrl, version.Version);
+
+            var summary = new
+            {
+                version = version.Version,
+                manifest = version.Manifest,
+                files = blobs.Count,
+                size,
+                tree = tree.Sha,
+                commit = refs?.Commit,
+                branch = refs?.Branch == null ? null : OrphanBranches.BranchName(version.Version),
+                url,
+                download,
+                details = $"api/versions/{version.Version}.json"
+            };
+            catalog.Add(summary);
+
+            files[$"api/versions/{version.Version}.json"] = JsonConvert.SerializeObject(new
+            {
+                summary,
+                diff,
+                files = blobs.Select(x => new
+                {
+                    path = x.Path,
+                    size = x.Blob.Size,
+                    assembly_version = x.Path.EndsWith(".dll", StringComparison.OrdinalIgnoreCase)
+                        ? AssemblyInfo.GetVersion(x.Blob.GetContentStream())
+                        : null
+                })
+            }, Formatting.Indented);
+
+            var changes = diff == null
+                ? "first version"
+                : $"+{diff.Added.Count} −{diff.Removed.Count} ~{diff.Modified.Count} vs {diff.From}";
+            var links = new[] { ("browse", url), ("download", download), ("json", summary.details) }
+                .Where(x => x.Item2 != null)
+                .Select(x => $"<a href=\"{WebUtility.HtmlEncode(x.Item2)}\">{x.Item1}</a>");
+            rows.AppendLine(
+                $"      <tr><td>{Html(version.Version)}</td><td><code>{Html(version.Manifest)}</code></td>" +
+                $"<td>{blobs.Count}</td><td>{Sizes.Format(size)}</td><td>{Html(changes)}</td>" +
+                $"<td><code>{Html(refs?.Commit?[..7] ?? "-")}</code></td><td>{string.Join(" · ", links)}</td></tr>");
+        }
+
+        files["api/versions.json"] = JsonConvert.SerializeObject(catalog, Formatting.Indented);
+        files["index.html"] = Assets.Render("site.index.html", new Dictionary<string, string>
+        {
+            ["title"] = Html(Environment.GetEnvironmentVariable("MBSS_SITE_TITLE") ?? "Beat Saber version mirror"),
+            ["count"] = catalog.Count.ToString(),
+            ["generated"] = DateTimeOffset.UtcNow.ToString("yyyy-MM-dd HH:mm 'UTC'"),
+            ["rows"] = rows.ToString().TrimEnd()
+        });
+        files[".nojekyll"] = string.Empty;
+
+        var output = Options.Value("--out");
+        if (output != null)
+        {
+            foreach (var (path, contents) in files)
+            {
+                var target = Path.Combine(output, path);
+                Directory.CreateDirectory(Path.GetDirectoryName(target)!);
+                await File.WriteAllTextAsync(target, contents);
+            }
+
+            AnsiConsole.MarkupLine($"[green]Rendered {catalog.Count} versions to {output.EscapeMarkup()}[/]");
+            return;
+        }
+
+        CommitBranch(repo, files, catalog.Count);
+    }
+
+    // The site branch is written straight to the object database, the checkout of main is never touched
+    private static void CommitBranch(Repository repo, Dictionary<string, string> files, int count)
+    {
+        var branch = Environment.GetEnvironmentVariable("MBSS_SITE_BRANCH") ?? "gh-pages";
+        var definition = new TreeDefinition();
+        foreach (var (path, contents) in files)
+        {
+            using var stream = new MemoryStream(Encoding.UTF8.GetBytes(contents));
+            definition.Add(path, repo.ObjectDatabase.CreateBlob(stream), Mode.NonExecutableFile);
+        }
+
+        var tree = repo.ObjectDatabase.CreateTree(definition);
+        var tip = repo.Branches[branch]?.Tip;
+        if (tip?.Tree.Id == tree.Id)
+        {
+            AnsiConsole.MarkupLine($"[yellow]{branch} is already up to date.[/]");
+            return;
+        }
+
+        var author = new Signature(Environment.GetEnvironmentVariable("GIT_AUTHOR_NAME") ?? "MBSS",
+            Environment.GetEnvironmentVariable("GIT_AUTHOR_EMAIL") ?? "mbss@localhost", DateTimeOffset.Now);
+        var parents = tip == null ? Array.Empty<Commit>() : new[] { tip };
+        var commit = repo.ObjectDatabase.CreateCommit(author, author, $"chore: publish site for {count} versions",
+            tree, parents, false);
+        repo.Refs.Add($"refs/heads/{branch}", commit.Id, true);
+        AnsiConsole.MarkupLine($"[green]Committed the site to {branch}[/]");
+
+        var remote = repo.Network.Remotes["origin"];
+        if (remote == null || Options.Offline) return;
+
+        repo.Network.Push(remote, $"refs/heads/{branch}",
+            new PushOptions { CredentialsProvider = Credentials.GitHandler });
+        AnsiConsole.MarkupLine($"[green]Pushed {branch}[/]");
+    }
+
+    private static string Html(string value)
+    {
+        return WebUtility.HtmlEncode(value);
+    }
+}
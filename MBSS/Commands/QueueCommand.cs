@@ -0,0 +1,38 @@
+﻿using Spectre.Console;
+
+namespace MBSS.Commands;
+
+internal static class QueueCommand
+{
+    public static void Run(string[] args)
+    {
+        using var queue = new JobQueue();
+        switch (args.FirstOrDefault())
+        {
+            case null:
+            case "list":
+                var table = new Table().AddColumns("Id", "Version", "Source", "Status", "Attempts", "Not Before",
+                    "Error");
+                foreach (var job in queue.List())
+                    table.AddRow(job.Id, (job.Version ?? "*").EscapeMarkup(), job.Source, job.Status,
+                        job.Attempts.ToString(), job.NotBefore.ToLocalTime().ToString("g"),
+                        (job.Error ?? "").EscapeMarkup());
+
+                AnsiConsole.Write(table);
+                break;
+            case "add":
+                var added = queue.Enqueue(args.ElementAtOrDefault(1), "cli");
+                AnsiConsole.MarkupLine(added == null
+                    ? "[yellow]An equivalent job is already queued.[/]"
+                    : $"[green]Queued job {added.Id}[/]");
+                break;
+            case "cancel" when args.Length > 1:
+                var removed = queue.Cancel(args[1]);
+                AnsiConsole.MarkupLine($"[green]Cancelled {removed} jobs[/]");
+                break;
+            default:
+                AnsiConsole.MarkupLine("[red]Usage: MBSS queue [list | add [version] | cancel <id | version | *>][/]");
+                break;
+        }
+    }
+}
@@ -0,0 +1,80 @@
+﻿using System.Security.Cryptography;
+using LibGit2Sharp;
+using Spectre.Console;
+
+namespace MBSS.Commands;
+
+internal static class RepairCommand
+{
+    // Files MBSS itself writes, a version with nothing but these was never stripped properly
+    private static readonly HashSet<string> GeneratedFiles = new(StringComparer.OrdinalIgnoreCase)
+    {
+        ".gitattributes", ".gitignore", "README.md", "version.txt", Checksums.FileName, Provenance.FileName
+    };
+
+    public static async Task<List<string>> FindBroken()
+    {
+        var versions = File.Exists("versions.json") ? await BeatSaberVersion.LoadAll() : null;
+        versions ??= new List<BeatSaberVersion>();
+        var verifyHashes = Options.Has("--hashes");
+
+        using var repo = new Repository(Workspace.RepositoryPath);
+        var index = VersionTree.CommitIndex(repo);
+        var broken = new List<string>();
+        var table = new Table().AddColumns("Version", "Problems");
+        foreach (var (version, refs) in index.OrderBy(x => x.Key, BeatSaberVersion.KeyComparer))
+        {
+            var tree = repo.Lookup<Tree>(refs.Tree);
+            var problems = Validate(repo, version, tree, refs, verifyHashes);
+            if (versions.All(x => x.Version != version)) problems.Add("not in versions.json, can't be rebuilt");
+            if (problems.Count == 0) continue;
+
+            table.AddRow(version.EscapeMarkup(), string.Join("\n", problems).EscapeMarkup());
+            if (versions.Any(x => x.Version == version)) broken.Add(version);
+        }
+
+        if (table.Rows.Count == 0)
+        {
+            AnsiConsole.MarkupLine($"[green]All {index.Count} versions are healthy![/]");
+            return broken;
+        }
+
+        AnsiConsole.Write(table);
+        return broken;
+    }
+
+    private static List<string> Validate(Repository repo, string version, Tree tree, VersionRefs refs,
+        bool verifyHashes)
+    {
+        var problems = new List<string>();
+        var files = VersionTree.Walk(tree).ToDictionary(x => x.Path, x => (Blob)x.Entry.Target);
+        if (files.Keys.All(GeneratedFiles.Contains)) problems.Add("no stripped files");
+
+        if (files.TryGetValue("version.txt", out var versionTxt) && versionTxt.GetContentText().Trim() != version)
+            problems.Add($"version.txt says {versionTxt.GetContentText().Trim()}");
+
+        if (refs.Branch != null && repo.Lookup<Commit>(refs.Branch)?.Tree.Sha != refs.Tree)
+            problems.Add($"{OrphanBranches.BranchName(version)} differs from main");
+
+        if (!verifyHashes || !files.TryGetValue(Checksums.FileName, out var sums)) return problems;
+
+        foreach (var line in sums.GetContentText().Split('\n', StringSplitOptions.RemoveEmptyEntries))
+        {
+            if (line.Length < 67) continue;
+
+            var hash = line[..64];
+            var path = line[66..];
+            if (!files.TryGetValue(path, out var blob))
+            {
+                problems.Add($"{path} is missing");
+                continue;
+            }
+
+            using var stream = blob.GetContentStream();
+            if (Convert.ToHexString(SHA256.HashData(stream)).ToLowerInvariant() != hash)
+                problems.Add($"{path} does not match {Checksums.FileName}");
+        }
+
+        return problems;
+    }
+}
@@ -0,0 +1,50 @@
+﻿using LibGit2Sharp;
+using Newtonsoft.Json;
+using Spectre.Console;
+
+namespace MBSS.Commands;
+
+internal static class ResolveCommand
+{
+    public static bool Run(string[] args)
+    {
+        if (args.Length != 1)
+        {
+            AnsiConsole.MarkupLine(
+                "[red]Usage: MBSS resolve <range> [[--platform <platform>]] [[--store oculus]] [[--format json]][/]");
+            return false;
+        }
+
+        // Ranges match the game version, other platforms and stores are only picked when asked for
+        var range = SemVerRange.Parse(args[0]);
+        var variant = BeatSaberVersion.VariantOf(Options.Value("--platform"), Options.Value("--store"));
+        using var repo = new Repository(Workspace.RepositoryPath);
+        var index = VersionTree.CommitIndex(repo);
+        var best = index.Keys
+            .Where(x => BeatSaberVersion.SplitKey(x).Variant == variant)
+            .Where(x => range.IsSatisfiedBy(GameSemVer(x)))
+            .MaxBy(GameSemVer);
+        if (best == null)
+        {
+            Console.Error.WriteLine($"No mirrored version satisfies {args[0]}");
+            return false;
+        }
+
+        // Orphan branches hold just the version, otherwise it's a directory on main
+        var refs = index[best];
+        var branch = refs.Branch != null ? OrphanBranches.BranchName(best) : "main";
+        var commit = refs.Branch ?? refs.Commit;
+
+        // Plain stdout without markup, this is meant to be captured by build scripts
+        Console.WriteLine(Options.Value("--format") == "json"
+            ? JsonConvert.SerializeObject(new { version = best, branch, commit, tree = refs.Tree },
+                Formatting.Indented)
+            : $"{best} {branch} {commit}");
+        return true;
+    }
+
+    private static SemVer GameSemVer(string version)
+    {
+        return SemVer.Parse(BeatSaberVersion.SplitKey(version).GameVersion);
+    }
+}
@@ -0,0 +1,45 @@
+﻿using LibGit2Sharp;
+using Spectre.Console;
+
+namespace MBSS.Commands;
+
+internal static class StatsCommand
+{
+    private const int LargestFileCount = 10;
+
+    public static void Run()
+    {
+        using var repo = new Repository(Workspace.RepositoryPath);
+        var versionsTree = VersionTree.MainTip(repo)?["versions"]?.Target as Tree;
+
+        var versions = new Table().AddColumns("Version", "Files", "Size");
+        var files = new List<(string Path, long Size)>();
+        var versionEntries = versionsTree?
+            .Where(x => x.Target is Tree)
+            .OrderBy(x => x.Name, BeatSaberVersion.KeyComparer);
+        foreach (var version in versionEntries ?? Enumerable.Empty<TreeEntry>())
+        {
+            var blobs = VersionTree.Walk((Tree)version.Target)
+                .Select(x => ($"{version.Name}/{x.Path}", ((Blob)x.Entry.Target).Size))
+                .ToList();
+
+            files.AddRange(blobs);
+            versions.AddRow(version.Name, blobs.Count.ToString(), Sizes.Format(blobs.Sum(x => x.Item2)));
+        }
+
+        AnsiConsole.Write(versions);
+
+        var largest = new Table().AddColumns("Largest Files", "Size");
+        foreach (var (path, size) in files.OrderByDescending(x => x.Size).Take(LargestFileCount))
+            largest.AddRow(path.EscapeMarkup(), Sizes.Format(size));
+
+        AnsiConsole.Write(largest);
+
+        var storage = new Table().AddColumns("Storage", "Size");
+        storage.AddRow("Git object store", Sizes.Format(Sizes.OfDirectory(Path.Combine(repo.Info.Path, "objects"))));
+        storage.AddRow("Download cache", Sizes.Format(Sizes.OfDirectory("downloads")));
+        storage.AddRow("Working tree versions", Sizes.Format(Sizes.OfDirectory("versions")));
+
+        AnsiConsole.Write(storage);
+    }
+}
@@ -0,0 +1,109 @@
+﻿using System.Security.Cryptography;
+using System.Text.RegularExpressions;
+using LibGit2Sharp;
+using MBSS.Sources;
+using MBSS.Tools;
+using Spectre.Console;
+
+namespace MBSS.Commands;
+
+internal static class VerifyCommand
+{
+    private static readonly Regex ManifestLineRegex =
+        new(@"^\s*(?<size>\d+)\s+\d+\s+(?<sha>[0-9a-fA-F]{40})\s+\d+\s+(?<name>.+?)\s*$");
+
+    public static async Task<bool> Run(HttpClient client, string[] args)
+    {
+        var versions = await BeatSaberVersion.LoadAll() ??
+                       throw new MbssException(ErrorKind.Config, "Failed to parse versions.json!");
+        if (args.Length > 0) versions = versions.Where(x => args.Contains(x.Version)).ToList();
+
+        Credentials.Load(SteamAccounts.CredentialEnvs);
+        if (!GitHubTool.DepotDownloader.IsInstalled) await GitHubTool.DepotDownloader.Install(client);
+
+        using var repo = new Repository(Workspace.RepositoryPath);
+        var source = new DepotDownloaderSource();
+        var healthy = true;
+
+        foreach (var version in versions)
+        {
+            if (version.IsOculus)
+            {
+                AnsiConsole.MarkupLine($"[yellow]Version {version.Version} is an Oculus build, skipping...[/]");
+                continue;
+            }
+
+            var tree = VersionTree.Find(repo, version.Version);
+            if (tree == null)
+            {
+                AnsiConsole.MarkupLine($"[yellow]Version {version.Version} is not in the repository, skipping...[/]");
+                continue;
+            }
+
+            var listingDir = Path.Combine(Path.GetTempPath(), $"mbss-verify-{version.Version}");
+            try
+            {
+                var listing = await source.FetchManifestListing(version, listingDir);
+                var problems = Verify(tree, await ReadManifest(listing));
+
+                foreach (var problem in problems)
+                    AnsiConsole.MarkupLine($"[red]{version.Version}: {problem.EscapeMarkup()}[/]");
+                if (problems.Count == 0) AnsiConsole.MarkupLine($"[green]Version {version.Version} verified![/]");
+                healthy &= problems.Count == 0;
+            }
+            finally
+            {
+                if (Directory.Exists(listingDir)) Directory.Delete(listingDir, true);
+            }
+        }
+
+        return healthy;
+    }
+
+    private static async Task<Dictionary<string, (long Size, string Sha)>> ReadManifest(string listing)
+    {
+        var files = new Dictionary<string, (long Size, string Sha)>(StringComparer.OrdinalIgnoreCase);
+        foreach (var line in await File.ReadAllLinesAsync(listing))
+        {
+            var match = ManifestLineRegex.Match(line);
+            if (!match.Success) continue;
+
+            files[match.Groups["name"].Value.Replace('\\', '/')] =
+                (long.Parse(match.Groups["size"].Value), match.Groups["sha"].Value.ToLowerInvariant());
+        }
+
+        return files;
+    }
+
+    private static List<string> Verify(Tree tree, Dictionary<string, (long Size, string Sha)> manifest)
+    {
+        var problems = new List<string>();
+        foreach (var (path, entry) in VersionTree.Walk(tree))
+        {
+            if (!manifest.TryGetValue(path, out var expected))
+            {
+                // Files MBSS writes itself aren't part of the depot
+                if (!path.Contains('/')) continue;
+
+                problems.Add($"{path} is not part of the depot manifest");
+                continue;
+            }
+
+            // Assemblies are rewritten by GenericStripper, so only their presence can be checked
+            if (path.EndsWith(".dll", StringComparison.OrdinalIgnoreCase)) continue;
+
+            var blob = (Blob)entry.Target;
+            if (blob.Size != expected.Size)
+            {
+                problems.Add($"{path} is {blob.Size} bytes, expected {expected.Size}");
+                continue;
+            }
+
+            using var stream = blob.GetContentStream();
+            var sha = Convert.ToHexString(SHA1.HashData(stream)).ToLowerInvariant();
+            if (sha != expected.Sha) problems.Add($"{path} has SHA1 {sha}, expected {expected.Sha}");
+        }
+
+        return problems;
+    }
+}
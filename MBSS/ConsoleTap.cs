@@ -0,0 +1,66 @@
+﻿using System.Text;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal static class ConsoleTap
+{
+    // Recreates the console around a wrapped writer, which Spectre would otherwise no longer detect as a terminal
+    public static void Attach(Func<TextWriter, TextWriter> wrap, bool interactive = true)
+    {
+        var profile = AnsiConsole.Profile;
+        interactive &= profile.Capabilities.Interactive;
+        var console = AnsiConsole.Create(new AnsiConsoleSettings
+        {
+            Ansi = profile.Capabilities.Ansi ? AnsiSupport.Yes : AnsiSupport.No,
+            ColorSystem = (ColorSystemSupport)profile.Capabilities.ColorSystem,
+            Interactive = interactive ? InteractionSupport.Yes : InteractionSupport.No,
+            Out = new AnsiConsoleOutput(wrap(profile.Out.Writer))
+        });
+        console.Profile.Width = profile.Width;
+        AnsiConsole.Console = console;
+    }
+
+    // Attached last, so the terminal, the log file, JSON lines and breadcrumbs all get the same redacted text
+    public static void Redact()
+    {
+        Attach(writer => new RedactingWriter(writer));
+    }
+
+    private class RedactingWriter : TextWriter
+    {
+        private readonly TextWriter _inner;
+        private readonly StringBuilder _pending = new();
+
+        public RedactingWriter(TextWriter inner)
+        {
+            _inner = inner;
+        }
+
+        public override Encoding Encoding => _inner.Encoding;
+
+        // Spectre flushes after every write, so a progress bar redraw is never held back waiting for a newline
+        public override void Write(char value)
+        {
+            lock (_pending)
+            {
+                _pending.Append(value);
+                if (value is '\n' or '\r') Drain();
+            }
+        }
+
+        public override void Flush()
+        {
+            lock (_pending) Drain();
+            _inner.Flush();
+        }
+
+        private void Drain()
+        {
+            if (_pending.Length == 0) return;
+
+            _inner.Write(Secrets.Redact(_pending.ToString()));
+            _pending.Clear();
+        }
+    }
+}
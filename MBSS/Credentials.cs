@@ -0,0 +1,124 @@
+﻿using System.Diagnostics;
+using System.Runtime.InteropServices;
+using LibGit2Sharp;
+using LibGit2Sharp.Handlers;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal static class Credentials
+{
+    private static readonly string[] SecretNames = { "STEAM_PASSWORD", "GITHUB_TOKEN" };
+
+    public static CredentialsHandler GitHandler => (_, _, _) => new UsernamePasswordCredentials
+    {
+        Username = Environment.GetEnvironmentVariable("GIT_AUTHOR_NAME"),
+        Password = Environment.GetEnvironmentVariable("GITHUB_TOKEN")
+    };
+
+    // Pruning drops remote-tracking branches whose branch is gone from the remote, e.g. after a migrate renamed them
+    public static FetchOptions GitFetchOptions => new() { CredentialsProvider = GitHandler, Prune = true };
+
+    public static void Load(IEnumerable<string> names)
+    {
+        var useKeyring = Environment.GetEnvironmentVariable("MBSS_KEYRING")?.ToLowerInvariant() is "1" or "true";
+
+        foreach (var name in names)
+        {
+            if (!string.IsNullOrEmpty(Environment.GetEnvironmentVariable(name))) continue;
+
+            var value = ReadFile(name);
+            if (value == null && useKeyring) value = ReadKeyring(name);
+            if (value == null && !Console.IsInputRedirected) value = Prompt(name);
+
+            if (!string.IsNullOrEmpty(value)) Environment.SetEnvironmentVariable(name, value);
+        }
+    }
+
+    private static string? ReadFile(string name)
+    {
+        var path = Environment.GetEnvironmentVariable($"{name}_FILE");
+        if (string.IsNullOrEmpty(path)) return null;
+        if (!File.Exists(path))
+            throw new MbssException(ErrorKind.Config, $"{name}_FILE points at {path}, which does not exist!");
+
+        return File.ReadAllText(path).Trim();
+    }
+
+    private static string Prompt(string name)
+    {
+        var prompt = new TextPrompt<string>($"[yellow]{name} is not set, enter it now:[/]").AllowEmpty();
+        if (SecretNames.Contains(name)) prompt.Secret();
+
+        return AnsiConsole.Prompt(prompt);
+    }
+
+    private static string? ReadKeyring(string name)
+    {
+        if (OperatingSystem.IsWindows()) return ReadWindowsCredential($"MBSS/{name}");
+
+        var lookup = OperatingSystem.IsMacOS()
+            ? new ProcessStartInfo("security")
+                { ArgumentList = { "find-generic-password", "-s", "mbss", "-a", name, "-w" } }
+            : new ProcessStartInfo("secret-tool")
+                { ArgumentList = { "lookup", "service", "mbss", "account", name } };
+        lookup.RedirectStandardOutput = true;
+        lookup.RedirectStandardError = true;
+        lookup.UseShellExecute = false;
+
+        try
+        {
+            using var process = Process.Start(lookup);
+            if (process == null) return null;
+
+            var output = process.StandardOutput.ReadToEnd().Trim();
+            process.WaitForExit();
+            return process.ExitCode == 0 && output.Length > 0 ? output : null;
+        }
+        catch (System.ComponentModel.Win32Exception)
+        {
+            AnsiConsole.MarkupLine($"[yellow]Keyring tool {lookup.FileName} is not available.[/]");
+            return null;
+        }
+    }
+
+    private static string? ReadWindowsCredential(string target)
+    {
+        if (!CredRead(target, 1, 0, out var handle)) return null;
+
+        try
+        {
+            var credential = Marshal.PtrToStructure<NativeCredential>(handle);
+            return credential.CredentialBlobSize == 0
+                ? null
+                : Marshal.PtrToStringUni(credential.CredentialBlob, credential.CredentialBlobSize / 2);
+        }
+        finally
+        {
+            CredFree(handle);
+        }
+    }
+
+    [DllImport("advapi32.dll", EntryPoint = "CredReadW", CharSet = CharSet.Unicode, SetLastError = true)]
+    private static extern bool CredRead(string target, int type, int flags, out IntPtr credential);
+
+    [DllImport("advapi32.dll")]
+    private static extern void CredFree(IntPtr credential);
+
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    private struct NativeCredential
+    {
+        public int Flags;
+        public int Type;
+        public string TargetName;
+        public string Comment;
+        public long LastWritten;
+        public int CredentialBlobSize;
+        public IntPtr CredentialBlob;
+        public int Persist;
+        public int AttributeCount;
+        public IntPtr Attributes;
+        public string TargetAlias;
+        public string UserName;
+    }
+}
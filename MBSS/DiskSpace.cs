@@ -0,0 +1,55 @@
+﻿namespace MBSS;
+
+internal static class DiskSpace
+{
+    private const long GigaByte = 1024L * 1024 * 1024;
+
+    // Download, stripped output and git objects for a full Beat Saber depot
+    private const long DefaultVersionSizeGb = 25;
+    private const long DefaultMarginGb = 10;
+
+    public static void CheckMargin(string path)
+    {
+        Ensure(path, 0, "MBSS");
+    }
+
+    public static void CheckVersion(string path, BeatSaberVersion version)
+    {
+        var estimate = ReadGb("MBSS_VERSION_SIZE_GB", DefaultVersionSizeGb);
+        Ensure(path, estimate, $"Version {version.Version}");
+    }
+
+    private static void Ensure(string path, long requiredGb, string what)
+    {
+        var margin = ReadGb("MBSS_MIN_FREE_GB", DefaultMarginGb);
+        var drive = MountOf(path);
+        var free = drive.AvailableFreeSpace;
+
+        if (free >= (requiredGb + margin) * GigaByte) return;
+
+        throw new MbssException(ErrorKind.DiskSpace,
+            $"{what} needs {requiredGb + margin} GB free on {drive.Name} ({requiredGb} GB + {margin} GB margin), " +
+            $"but only {free / GigaByte} GB is available!");
+    }
+
+    // The path root is always / on Linux, downloads/ or the repository often sit on a volume mounted below it
+    private static DriveInfo MountOf(string path)
+    {
+        var comparison = OperatingSystem.IsWindows() ? StringComparison.OrdinalIgnoreCase : StringComparison.Ordinal;
+        var fullPath = WithSeparator(Path.GetFullPath(path));
+        return DriveInfo.GetDrives()
+                   .Where(x => fullPath.StartsWith(WithSeparator(x.RootDirectory.FullName), comparison))
+                   .MaxBy(x => x.RootDirectory.FullName.Length) ??
+               new DriveInfo(Path.GetPathRoot(fullPath)!);
+    }
+
+    private static string WithSeparator(string path)
+    {
+        return path.EndsWith(Path.DirectorySeparatorChar) ? path : path + Path.DirectorySeparatorChar;
+    }
+
+    private static long ReadGb(string env, long fallback)
+    {
+        return long.TryParse(Environment.GetEnvironmentVariable(env), out var value) ? value : fallback;
+    }
+}
@@ -0,0 +1,46 @@
+﻿namespace MBSS;
+
+// A daily range of local time, like 01:00-06:00, outside of which the daemon holds back queued jobs
+internal class DownloadWindow
+{
+    private readonly TimeSpan _start;
+    private readonly TimeSpan _end;
+
+    private DownloadWindow(TimeSpan start, TimeSpan end)
+    {
+        _start = start;
+        _end = end;
+    }
+
+    public static DownloadWindow? FromEnvironment()
+    {
+        var value = Environment.GetEnvironmentVariable("MBSS_DOWNLOAD_WINDOW");
+        if (string.IsNullOrEmpty(value)) return null;
+
+        var parts = value.Split('-', 2, StringSplitOptions.TrimEntries);
+        if (parts.Length != 2 || !TimeSpan.TryParse(parts[0], out var start) ||
+            !TimeSpan.TryParse(parts[1], out var end) || start == end)
+            throw new MbssException(ErrorKind.Config, $"Invalid MBSS_DOWNLOAD_WINDOW {value}, expected HH:mm-HH:mm!");
+
+        return new DownloadWindow(start, end);
+    }
+
+    public bool IsOpen(DateTime now)
+    {
+        var time = now.TimeOfDay;
+
+        // Windows like 22:00-04:00 wrap around midnight
+        return _start < _end ? time >= _start && time < _end : time >= _start || time < _end;
+    }
+
+    public DateTime NextOpen(DateTime now)
+    {
+        var today = now.Date + _start;
+        return today > now ? today : today.AddDays(1);
+    }
+
+    public override string ToString()
+    {
+        return $@"{_start:hh\:mm}-{_end:hh\:mm}";
+    }
+}
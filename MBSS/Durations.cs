@@ -0,0 +1,34 @@
+﻿using System.Globalization;
+
+namespace MBSS;
+
+internal static class Durations
+{
+    // Plain numbers are seconds, otherwise a single s/m/h/d suffix
+    public static TimeSpan Parse(string value, string description)
+    {
+        var unit = value[^1];
+        var number = char.IsLetter(unit) ? value[..^1] : value;
+        if (!double.TryParse(number, NumberStyles.Float, CultureInfo.InvariantCulture, out var amount) || amount <= 0)
+            throw new MbssException(ErrorKind.Config, $"Invalid {description} {value}!");
+
+        return unit switch
+        {
+            's' => TimeSpan.FromSeconds(amount),
+            'm' => TimeSpan.FromMinutes(amount),
+            'h' => TimeSpan.FromHours(amount),
+            'd' => TimeSpan.FromDays(amount),
+            _ when char.IsDigit(unit) => TimeSpan.FromSeconds(amount),
+            _ => throw new MbssException(ErrorKind.Config, $"Invalid {description} {value}!")
+        };
+    }
+
+    public static TimeSpan? FromEnvironment(string variable, TimeSpan? fallback = null)
+    {
+        var value = Environment.GetEnvironmentVariable(variable);
+        if (string.IsNullOrEmpty(value)) return fallback;
+
+        // 0 turns a limit with a default off
+        return value == "0" ? null : Parse(value, variable);
+    }
+}
@@ -0,0 +1,145 @@
+﻿using System.Text;
+using System.Text.RegularExpressions;
+using Newtonsoft.Json;
+using Sentry;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal static class ErrorReporter
+{
+    private const int MaxBreadcrumbs = 50;
+
+    private static readonly Queue<string> Breadcrumbs = new();
+
+    private static string? WebhookUrl => Environment.GetEnvironmentVariable("MBSS_ERROR_WEBHOOK_URL");
+    private static string? SentryDsn => Environment.GetEnvironmentVariable("SENTRY_DSN");
+
+    public static void Init()
+    {
+        if (string.IsNullOrEmpty(WebhookUrl) && string.IsNullOrEmpty(SentryDsn)) return;
+
+        if (!string.IsNullOrEmpty(SentryDsn))
+            SentrySdk.Init(options =>
+            {
+                options.Dsn = SentryDsn;
+                options.MaxBreadcrumbs = MaxBreadcrumbs;
+                options.SetBeforeSend(Redact);
+            });
+
+        ConsoleTap.Attach(writer => new BreadcrumbWriter(writer));
+    }
+
+    public static async Task Report(Exception? exception)
+    {
+        if (Options.Offline) return;
+
+        var failed = RunReport.Versions.Where(x => x.Status == "failed").ToList();
+        var message = exception?.Message ?? $"{failed.Count} versions failed to process";
+
+        if (!string.IsNullOrEmpty(SentryDsn))
+        {
+            SentrySdk.ConfigureScope(scope =>
+            {
+                scope.SetTag("mbss.versions", string.Join(",", failed.Select(x => x.Version)));
+                foreach (var version in failed)
+                    scope.SetExtra($"error.{version.Version}", Secrets.Redact(version.Error ?? string.Empty));
+            });
+
+            if (exception != null) SentrySdk.CaptureException(exception);
+            else SentrySdk.CaptureMessage(message, SentryLevel.Error);
+            await SentrySdk.FlushAsync(TimeSpan.FromSeconds(10));
+        }
+
+        if (string.IsNullOrEmpty(WebhookUrl)) return;
+
+        var errors = new List<string>();
+        for (var e = exception; e != null; e = e.InnerException) errors.Add(e.Message);
+
+        string[] breadcrumbs;
+        lock (Breadcrumbs) breadcrumbs = Breadcrumbs.ToArray();
+
+        var payload = JsonConvert.SerializeObject(new
+        {
+            message = Secrets.Redact(message),
+            errors = errors.Select(Secrets.Redact),
+            versions = failed.Select(x => new { version = x.Version, error = Secrets.Redact(x.Error ?? string.Empty) }),
+            host = Environment.MachineName,
+            breadcrumbs
+        });
+
+        try
+        {
+            using var client = Http.CreateClient();
+            var res = await client.PostAsync(WebhookUrl, new StringContent(payload, Encoding.UTF8, "application/json"));
+            if (!res.IsSuccessStatusCode)
+                AnsiConsole.MarkupLine($"[yellow]Error webhook rejected the report ({(int)res.StatusCode}).[/]");
+        }
+        catch (HttpRequestException e)
+        {
+            AnsiConsole.MarkupLine($"[yellow]Failed to send the error report: {e.Message.EscapeMarkup()}[/]");
+        }
+    }
+
+    // Exception messages can quote command lines and URLs, secrets are taken out before anything leaves the machine
+    private static SentryEvent Redact(SentryEvent sentryEvent)
+    {
+        if (sentryEvent.Message is { } message)
+        {
+            if (message.Message != null) message.Message = Secrets.Redact(message.Message);
+            if (message.Formatted != null) message.Formatted = Secrets.Redact(message.Formatted);
+        }
+
+        foreach (var exception in sentryEvent.SentryExceptions ?? Enumerable.Empty<SentryException>())
+            if (exception.Value != null)
+                exception.Value = Secrets.Redact(exception.Value);
+
+        foreach (var (key, value) in sentryEvent.Extra.ToList())
+            if (value is string text)
+                sentryEvent.SetExtra(key, Secrets.Redact(text));
+
+        return sentryEvent;
+    }
+
+    private class BreadcrumbWriter : TextWriter
+    {
+        private static readonly Regex EscapeRegex = new(@"\x1b\[[0-9;?]*[A-Za-z]");
+
+        private readonly TextWriter _console;
+        private readonly StringBuilder _line = new();
+
+        public BreadcrumbWriter(TextWriter console)
+        {
+            _console = console;
+        }
+
+        public override Encoding Encoding => _console.Encoding;
+
+        public override void Write(char value)
+        {
+            _console.Write(value);
+            if (value == '\r') return;
+            if (value != '\n')
+            {
+                _line.Append(value);
+                return;
+            }
+
+            var line = Secrets.Redact(EscapeRegex.Replace(_line.ToString(), string.Empty)).TrimEnd();
+            _line.Clear();
+            if (line.Length == 0) return;
+
+            if (!string.IsNullOrEmpty(SentryDsn)) SentrySdk.AddBreadcrumb(line);
+            lock (Breadcrumbs)
+            {
+                Breadcrumbs.Enqueue(line);
+                if (Breadcrumbs.Count > MaxBreadcrumbs) Breadcrumbs.Dequeue();
+            }
+        }
+
+        public override void Flush()
+        {
+            _console.Flush();
+        }
+    }
+}
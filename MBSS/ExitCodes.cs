@@ -0,0 +1,42 @@
+﻿namespace MBSS;
+
+// What CI can rely on:
+//   0  everything succeeded, including runs with nothing new to mirror
+//   1  unexpected failure, or a command that reported failure (checkout, resolve, exists, doctor, verify)
+//   2  some versions failed while others went through (--keep-going)
+//   3  configuration error or a full disk, fix the environment or versions.json before running again, also used
+//      when exists --remote can't reach origin or import finds the version already mirrored
+//   4  Steam rejected the account or it doesn't own the game
+internal static class ExitCodes
+{
+    public const int Success = 0;
+    public const int Failure = 1;
+    public const int PartialFailure = 2;
+    public const int Config = 3;
+    public const int Auth = 4;
+
+    public static int For(Exception e)
+    {
+        return For(MbssException.KindOf(e));
+    }
+
+    public static int For(List<VersionReport> versions)
+    {
+        var failed = versions.Where(x => x.Status == "failed").ToList();
+        if (failed.Count == 0) return Success;
+
+        // A shared cause like a bad password fails every version the same way, which is worth reporting as such
+        var codes = failed.Select(x => x.ErrorKind is { } kind ? For(kind) : Failure).Distinct().ToList();
+        return codes.Count == 1 && codes[0] != Failure ? codes[0] : PartialFailure;
+    }
+
+    private static int For(ErrorKind kind)
+    {
+        return kind switch
+        {
+            ErrorKind.Config or ErrorKind.DiskSpace => Config,
+            ErrorKind.SteamAuth => Auth,
+            _ => Failure
+        };
+    }
+}
@@ -0,0 +1,141 @@
+﻿using System.Runtime.InteropServices;
+
+namespace MBSS;
+
+// Reflinks share extents copy-on-write, hardlinks share the inode, both avoid duplicating gigabytes of depot data
+internal static class FileCopy
+{
+    private const ulong Ficlone = 0x40049409;
+
+    private static string Strategy =>
+        Environment.GetEnvironmentVariable("MBSS_COPY_STRATEGY")?.ToLowerInvariant() ?? "auto";
+
+    // Returns the strategy that ended up being used, so callers can tell whether any space was shared
+    public static string Copy(string source, string destination, bool allowHardlink = true)
+    {
+        if (File.Exists(destination)) File.Delete(destination);
+
+        var strategy = Strategy;
+        if (strategy is "auto" or "reflink" && TryReflink(source, destination))
+        {
+            // Clones are new files and start out with the default mode
+            if (!OperatingSystem.IsWindows()) File.SetUnixFileMode(destination, File.GetUnixFileMode(source));
+            return "reflink";
+        }
+
+        if (strategy is "auto" or "hardlink" && allowHardlink && TryHardlink(source, destination)) return "hardlink";
+
+        File.Copy(source, destination, true);
+        return "copy";
+    }
+
+    public static void CopyDirectory(string source, string destination, bool allowHardlink = true)
+    {
+        foreach (var file in EnumerateFiles(source))
+        {
+            var target = Path.Combine(destination, Path.GetRelativePath(source, file));
+            Directory.CreateDirectory(Path.GetDirectoryName(target)!);
+
+            // Links are recreated as links, following them could pull in files from outside the depot
+            var linkTarget = new FileInfo(file).LinkTarget;
+            if (linkTarget != null)
+            {
+                if (File.Exists(target) || new FileInfo(target).LinkTarget != null) File.Delete(target);
+                File.CreateSymbolicLink(target, linkTarget);
+                continue;
+            }
+
+            Copy(file, target, allowHardlink);
+            if (!OperatingSystem.IsWindows()) File.SetUnixFileMode(target, File.GetUnixFileMode(file));
+        }
+    }
+
+    // Like Directory.EnumerateFiles, but symlinks (to directories as well) are returned instead of followed
+    public static IEnumerable<string> EnumerateFiles(string directory)
+    {
+        foreach (var entry in new DirectoryInfo(directory).EnumerateFileSystemInfos())
+        {
+            if (entry.LinkTarget == null && entry is DirectoryInfo subdirectory)
+            {
+                foreach (var file in EnumerateFiles(subdirectory.FullName)) yield return file;
+            }
+            else
+            {
+                yield return entry.FullName;
+            }
+        }
+    }
+
+    public static bool IsSymlink(string path)
+    {
+        return new FileInfo(path).LinkTarget != null;
+    }
+
+    public static bool IsExecutable(string path)
+    {
+        const UnixFileMode execute = UnixFileMode.UserExecute | UnixFileMode.GroupExecute | UnixFileMode.OtherExecute;
+        return !OperatingSystem.IsWindows() && (File.GetUnixFileMode(path) & execute) != 0;
+    }
+
+    // Renames only work within a filesystem, MBSS_WORK_DIR and downloads/ don't have to share one
+    public static void MoveDirectory(string source, string destination)
+    {
+        try
+        {
+            Directory.Move(source, destination);
+        }
+        catch (IOException) when (Directory.Exists(source) && !Directory.Exists(destination))
+        {
+            CopyDirectory(source, destination);
+            Directory.Delete(source, true);
+        }
+    }
+
+    private static bool TryReflink(string source, string destination)
+    {
+        try
+        {
+            if (OperatingSystem.IsMacOS()) return clonefile(source, destination, 0) == 0;
+            if (!OperatingSystem.IsLinux()) return false;
+
+            using var input = File.OpenRead(source);
+            using var output = File.Create(destination);
+            if (ioctl((int)output.SafeFileHandle.DangerousGetHandle(), Ficlone,
+                    (int)input.SafeFileHandle.DangerousGetHandle()) == 0) return true;
+        }
+        catch (Exception e) when (e is DllNotFoundException or EntryPointNotFoundException)
+        {
+            return false;
+        }
+
+        // A failed clone leaves an empty file behind
+        File.Delete(destination);
+        return false;
+    }
+
+    private static bool TryHardlink(string source, string destination)
+    {
+        try
+        {
+            return OperatingSystem.IsWindows()
+                ? CreateHardLink(LongPaths.Extended(destination), LongPaths.Extended(source), IntPtr.Zero)
+                : link(source, destination) == 0;
+        }
+        catch (Exception e) when (e is DllNotFoundException or EntryPointNotFoundException)
+        {
+            return false;
+        }
+    }
+
+    [DllImport("libc", SetLastError = true)]
+    private static extern int ioctl(int fd, ulong request, int sourceFd);
+
+    [DllImport("libc", SetLastError = true)]
+    private static extern int clonefile(string source, string destination, int flags);
+
+    [DllImport("libc", SetLastError = true)]
+    private static extern int link(string source, string destination);
+
+    [DllImport("kernel32.dll", EntryPoint = "CreateHardLinkW", CharSet = CharSet.Unicode, SetLastError = true)]
+    private static extern bool CreateHardLink(string fileName, string existingFileName, IntPtr securityAttributes);
+}
@@ -0,0 +1,94 @@
+﻿using System.Text;
+using System.Text.RegularExpressions;
+using Microsoft.Extensions.FileSystemGlobbing;
+
+namespace MBSS;
+
+internal class FileFilter
+{
+    private static readonly string[] DefaultExcludes =
+        { ".DepotDownloader/**", "**/*.log", "**/.DS_Store", "**/Thumbs.db", "**/desktop.ini" };
+
+    private readonly Matcher _excludes = new(StringComparison.OrdinalIgnoreCase);
+    private readonly Matcher? _includes;
+    private readonly string[]? _includeGlobs;
+
+    private FileFilter(string[]? includes, IEnumerable<string> excludes)
+    {
+        _includeGlobs = includes;
+        if (includes != null)
+        {
+            _includes = new Matcher(StringComparison.OrdinalIgnoreCase);
+            _includes.AddIncludePatterns(includes);
+        }
+
+        _excludes.AddIncludePatterns(excludes);
+    }
+
+    public static FileFilter FromEnvironment()
+    {
+        return new FileFilter(ReadGlobs("MBSS_INCLUDE"), ReadGlobs("MBSS_EXCLUDE") ?? DefaultExcludes);
+    }
+
+    public bool IsIncluded(string root, string file)
+    {
+        if (_includes != null && !_includes.Match(root, file).HasMatches) return false;
+        return !_excludes.Match(root, file).HasMatches;
+    }
+
+    public string? ToDepotFileList()
+    {
+        return _includeGlobs == null
+            ? null
+            : string.Join('\n', _includeGlobs.Select(glob => $"regex:{GlobToRegex(glob)}"));
+    }
+
+    public void Apply(string root)
+    {
+        foreach (var file in Directory.EnumerateFiles(root, "*", SearchOption.AllDirectories).ToList()
+                     .Where(file => !IsIncluded(root, file)))
+            File.Delete(file);
+
+        foreach (var dir in Directory.EnumerateDirectories(root, "*", SearchOption.AllDirectories)
+                     .OrderByDescending(x => x.Length).ToList()
+                     .Where(dir => !Directory.EnumerateFileSystemEntries(dir).Any()))
+            Directory.Delete(dir);
+    }
+
+    private static string GlobToRegex(string glob)
+    {
+        var regex = new StringBuilder("^");
+        for (var i = 0; i < glob.Length; i++)
+        {
+            var c = glob[i];
+            if (c == '*' && i + 1 < glob.Length && glob[i + 1] == '*')
+            {
+                var slash = i + 2 < glob.Length && glob[i + 2] == '/';
+                regex.Append(slash ? "(.*/)?" : ".*");
+                i += slash ? 2 : 1;
+            }
+            else if (c == '*')
+            {
+                regex.Append("[^/]*");
+            }
+            else if (c == '?')
+            {
+                regex.Append("[^/]");
+            }
+            else
+            {
+                regex.Append(Regex.Escape(c.ToString()));
+            }
+        }
+
+        return regex.Append('$').ToString();
+    }
+
+    private static string[]? ReadGlobs(string env)
+    {
+        var value = Environment.GetEnvironmentVariable(env);
+        return string.IsNullOrEmpty(value)
+            ? null
+            : value.Split(',', StringSplitOptions.RemoveEmptyEntries | StringSplitOptions.TrimEntries);
+    }
+}
@@ -0,0 +1,86 @@
+﻿using System.Text;
+
+namespace MBSS;
+
+internal static class GitHubActions
+{
+    public static bool Enabled { get; private set; }
+
+    public static void Init()
+    {
+        var ci = Options.Value("--ci");
+        if (ci == null) return;
+        if (ci != "github") throw new MbssException(ErrorKind.Config, $"Unsupported CI provider {ci}!");
+
+        Enabled = true;
+    }
+
+    public static void Group(string title)
+    {
+        Command("group", title);
+    }
+
+    public static void EndGroup()
+    {
+        Command("endgroup", string.Empty);
+    }
+
+    public static void Error(string message, string? title = null)
+    {
+        Command(title == null ? "error" : $"error title={Escape(title)}", message);
+    }
+
+    public static async Task Finish(IReadOnlyList<VersionReport> versions)
+    {
+        if (!Enabled) return;
+
+        var committed = versions.Where(x => x.Status == "committed").ToList();
+        await Append("GITHUB_OUTPUT", new StringBuilder()
+            .AppendLine($"new_versions={string.Join(",", committed.Select(x => x.Version))}")
+            .AppendLine($"latest_commit={committed.LastOrDefault(x => x.Commit != null)?.Commit}")
+            .AppendLine($"failed={versions.Any(x => x.Status == "failed").ToString().ToLowerInvariant()}")
+            .ToString());
+
+        var processed = versions.Where(x => x.Status != "skipped").ToList();
+        var summary = new StringBuilder().AppendLine("## MBSS").AppendLine();
+        if (processed.Count == 0)
+        {
+            summary.AppendLine("No new versions were processed.");
+        }
+        else
+        {
+            summary.AppendLine("| Version | Status | Downloaded | Commit | Pushed |");
+            summary.AppendLine("| --- | --- | --- | --- | --- |");
+            foreach (var version in processed)
+            {
+                var status = version.Status == "failed" ? $"❌ {version.Error?.Replace("|", "\\|")}" : version.Status;
+                summary.AppendLine($"| {version.Version} | {status} | {Sizes.Format(version.DownloadedBytes)} | " +
+                                   $"{version.Commit?[..7] ?? "-"} | {(version.Pushed ? "yes" : "no")} |");
+            }
+        }
+
+        await Append("GITHUB_STEP_SUMMARY", summary.ToString());
+    }
+
+    private static void Command(string command, string message)
+    {
+        if (!Enabled) return;
+
+        // Workflow commands are only recognized at the start of a raw stdout line, so they bypass the console
+        Console.Out.WriteLine($"::{command}::{Escape(Secrets.Redact(message))}");
+        Console.Out.Flush();
+    }
+
+    private static async Task Append(string variable, string contents)
+    {
+        var path = Environment.GetEnvironmentVariable(variable);
+        if (string.IsNullOrEmpty(path)) return;
+
+        await File.AppendAllTextAsync(path, contents);
+    }
+
+    private static string Escape(string value)
+    {
+        return value.Replace("%", "%25").Replace("\r", "%0D").Replace("\n", "%0A");
+    }
+}
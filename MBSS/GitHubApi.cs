@@ -0,0 +1,68 @@
+﻿using System.Net;
+using System.Net.Http.Headers;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal static class GitHubApi
+{
+    private const int DefaultMaxWaitSeconds = 300;
+
+    public static Task<HttpResponseMessage> Get(HttpClient client, string url)
+    {
+        return Send(client, HttpMethod.Get, url);
+    }
+
+    // Content is created per attempt, a request body can't be sent again after a rate limited response
+    public static async Task<HttpResponseMessage> Send(HttpClient client, HttpMethod method, string url,
+        Func<HttpContent>? content = null)
+    {
+        var maxWait = int.TryParse(Environment.GetEnvironmentVariable("MBSS_RATE_LIMIT_MAX_WAIT"), out var seconds)
+            ? seconds
+            : DefaultMaxWaitSeconds;
+
+        while (true)
+        {
+            var req = new HttpRequestMessage(method, url) { Content = content?.Invoke() };
+            req.Headers.Accept.Add(new MediaTypeWithQualityHeaderValue("application/vnd.github+json"));
+
+            var token = Environment.GetEnvironmentVariable("GITHUB_TOKEN");
+            if (!string.IsNullOrEmpty(token))
+                req.Headers.Authorization = new AuthenticationHeaderValue("Bearer", token);
+
+            var res = await client.SendAsync(req);
+            var reset = GetRateLimitReset(res);
+            if (reset == null) return res;
+
+            var wait = reset.Value - DateTimeOffset.UtcNow;
+            if (wait.TotalSeconds > maxWait)
+                throw new RateLimitException(
+                    $"GitHub API rate limit exceeded until {reset.Value.ToLocalTime():t}, set GITHUB_TOKEN!");
+
+            AnsiConsole.MarkupLine($"[yellow]GitHub API rate limit exceeded, retrying in {wait.TotalSeconds:0}s...[/]");
+            await Task.Delay(wait > TimeSpan.Zero ? wait : TimeSpan.FromSeconds(1));
+        }
+    }
+
+    private static DateTimeOffset? GetRateLimitReset(HttpResponseMessage res)
+    {
+        if (res.StatusCode is not (HttpStatusCode.Forbidden or HttpStatusCode.TooManyRequests)) return null;
+
+        if (res.Headers.RetryAfter?.Delta is { } retryAfter) return DateTimeOffset.UtcNow + retryAfter;
+
+        if (!res.Headers.TryGetValues("X-RateLimit-Remaining", out var remaining) || remaining.First() != "0")
+            return null;
+
+        return res.Headers.TryGetValues("X-RateLimit-Reset", out var reset) &&
+               long.TryParse(reset.First(), out var epoch)
+            ? DateTimeOffset.FromUnixTimeSeconds(epoch)
+            : DateTimeOffset.UtcNow.AddMinutes(1);
+    }
+}
+
+internal class RateLimitException : MbssException
+{
+    public RateLimitException(string message) : base(ErrorKind.RateLimited, message)
+    {
+    }
+}
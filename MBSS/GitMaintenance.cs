@@ -0,0 +1,44 @@
+﻿using System.ComponentModel;
+using System.Diagnostics;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal static class GitMaintenance
+{
+    public static bool AutoEnabled => Environment.GetEnvironmentVariable("MBSS_AUTO_GC")?.ToLowerInvariant() is not
+        ("0" or "false");
+
+    public static async Task Run()
+    {
+        AnsiConsole.MarkupLine("[yellow]Repacking the repository and pruning unreachable objects...[/]");
+
+        // libgit2 can't repack, so this relies on the git CLI being available
+        var gc = new Process
+        {
+            StartInfo =
+            {
+                FileName = "git",
+                ArgumentList = { "-c", "pack.window=250", "-c", "pack.depth=50", "gc", "--prune=now" },
+                WorkingDirectory = Workspace.RepositoryPath,
+                UseShellExecute = false
+            }
+        };
+
+        try
+        {
+            gc.Start();
+        }
+        catch (Win32Exception)
+        {
+            AnsiConsole.MarkupLine("[yellow]git is not installed, skipping garbage collection.[/]");
+            return;
+        }
+
+        await gc.WaitForExitAsync();
+        if (gc.ExitCode != 0)
+            throw new MbssException(ErrorKind.Repository, $"git gc failed with exit code {gc.ExitCode}!");
+
+        AnsiConsole.MarkupLine("[green]Repository repacked![/]");
+    }
+}
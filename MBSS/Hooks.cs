@@ -0,0 +1,36 @@
+﻿using Spectre.Console;
+
+namespace MBSS;
+
+internal static class Hooks
+{
+    public static async Task Run(string hook, BeatSaberVersion version, string versionPath)
+    {
+        var command = Environment.GetEnvironmentVariable($"MBSS_HOOK_{hook.Replace('-', '_').ToUpperInvariant()}");
+        if (string.IsNullOrEmpty(command)) return;
+
+        AnsiConsole.MarkupLine($"[yellow]Running {hook} hook for version {version.Version}...[/]");
+
+        var env = ContextEnvironment(version, versionPath);
+        env["MBSS_HOOK"] = hook;
+
+        var exitCode = await Shell.Run(command, env);
+        if (exitCode == 0) return;
+
+        // Nothing can be undone after pushing, so only earlier hooks get to abort the version
+        if (hook == "post-push")
+            AnsiConsole.MarkupLine($"[red]The {hook} hook failed with exit code {exitCode}![/]");
+        else
+            throw new MbssException(ErrorKind.Hook, $"The {hook} hook failed with exit code {exitCode}!");
+    }
+
+    public static Dictionary<string, string> ContextEnvironment(BeatSaberVersion version, string versionPath)
+    {
+        return new Dictionary<string, string>
+        {
+            ["MBSS_VERSION"] = version.Version,
+            ["MBSS_MANIFEST"] = version.Manifest,
+            ["MBSS_VERSION_PATH"] = versionPath
+        };
+    }
+}
@@ -0,0 +1,42 @@
+﻿using System.Net;
+using System.Net.Security;
+using System.Security.Cryptography.X509Certificates;
+
+namespace MBSS;
+
+internal static class Http
+{
+    public static HttpClient CreateClient()
+    {
+        var handler = new HttpClientHandler();
+
+        // HTTP(S)_PROXY and NO_PROXY are honored by the default proxy, MBSS_PROXY takes precedence over them
+        var proxy = Environment.GetEnvironmentVariable("MBSS_PROXY");
+        if (!string.IsNullOrEmpty(proxy)) handler.Proxy = new WebProxy(proxy);
+
+        var caBundle = Environment.GetEnvironmentVariable("MBSS_CA_BUNDLE");
+        if (!string.IsNullOrEmpty(caBundle))
+        {
+            if (!File.Exists(caBundle))
+                throw new MbssException(ErrorKind.Config, $"CA bundle {caBundle} does not exist!");
+
+            var roots = new X509Certificate2Collection();
+            roots.ImportFromPemFile(caBundle);
+
+            handler.ServerCertificateCustomValidationCallback = (_, cert, chain, errors) =>
+            {
+                if (errors == SslPolicyErrors.None) return true;
+                if (cert == null || chain == null || errors != SslPolicyErrors.RemoteCertificateChainErrors)
+                    return false;
+
+                chain.ChainPolicy.TrustMode = X509ChainTrustMode.CustomRootTrust;
+                chain.ChainPolicy.CustomTrustStore.AddRange(roots);
+                return chain.Build(cert);
+            };
+        }
+
+        var client = new HttpClient(handler);
+        client.DefaultRequestHeaders.Add("User-Agent", "MBSS");
+        return client;
+    }
+}
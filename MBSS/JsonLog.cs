@@ -0,0 +1,70 @@
+﻿using System.Text;
+using System.Text.RegularExpressions;
+using Newtonsoft.Json;
+using Newtonsoft.Json.Linq;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal class JsonLog : TextWriter
+{
+    private static readonly Regex AnsiRegex = new(@"\x1b\[(?<codes>[0-9;]*)m");
+
+    private readonly TextWriter _inner;
+    private readonly StringBuilder _line = new();
+
+    private JsonLog(TextWriter inner)
+    {
+        _inner = inner;
+    }
+
+    public override Encoding Encoding => _inner.Encoding;
+
+    public static void Init()
+    {
+        if (Options.Value("--log-format") != "json") return;
+
+        // Everything is still rendered by Spectre, the colors it emits are what tell the log levels apart
+        AnsiConsole.Console = AnsiConsole.Create(new AnsiConsoleSettings
+        {
+            Ansi = AnsiSupport.Yes,
+            ColorSystem = ColorSystemSupport.Standard,
+            Interactive = InteractionSupport.No,
+            Out = new AnsiConsoleOutput(new JsonLog(Console.Out))
+        });
+    }
+
+    public override void Write(char value)
+    {
+        if (value == '\r') return;
+        if (value != '\n')
+        {
+            _line.Append(value);
+            return;
+        }
+
+        var line = _line.ToString();
+        _line.Clear();
+
+        var message = AnsiRegex.Replace(line, string.Empty);
+        if (string.IsNullOrWhiteSpace(message)) return;
+
+        var codes = AnsiRegex.Matches(line).SelectMany(x => x.Groups["codes"].Value.Split(';')).ToList();
+        var level = codes.Any(x => x is "31" or "91") ? "error" : codes.Any(x => x is "33" or "93") ? "warn" : "info";
+
+        var obj = new JObject
+        {
+            ["time"] = DateTimeOffset.UtcNow,
+            ["level"] = level,
+            ["message"] = message.TrimEnd()
+        };
+        if (RunReport.Current != null) obj["version"] = RunReport.Current.Version;
+        if (RunReport.ActiveStage is (var stage, var seconds))
+        {
+            obj["stage"] = stage;
+            obj["duration"] = Math.Round(seconds, 3);
+        }
+
+        lock (_inner) _inner.WriteLine(obj.ToString(Formatting.None));
+    }
+}
@@ -0,0 +1,54 @@
+﻿using Spectre.Console;
+
+namespace MBSS;
+
+internal static class LargeFiles
+{
+    // GitHub rejects pushes containing files over 100 MB
+    private const long DefaultThresholdMb = 100;
+
+    public static string Policy => Environment.GetEnvironmentVariable("MBSS_LARGE_FILE_POLICY")?.ToLowerInvariant() ??
+                                   "warn";
+
+    public static async Task Check(string versionPath)
+    {
+        if (Policy == "off") return;
+
+        var thresholdMb = long.TryParse(Environment.GetEnvironmentVariable("MBSS_LARGE_FILE_SIZE_MB"), out var mb)
+            ? mb
+            : DefaultThresholdMb;
+
+        var largeFiles = Directory.EnumerateFiles(versionPath, "*", SearchOption.AllDirectories)
+            .Select(x => new FileInfo(x))
+            .Where(x => x.Length > thresholdMb * 1024 * 1024)
+            .ToList();
+        if (largeFiles.Count == 0) return;
+
+        foreach (var file in largeFiles)
+        {
+            var relative = Path.GetRelativePath(versionPath, file.FullName).Replace('\\', '/');
+            AnsiConsole.MarkupLine($"[yellow]{relative.EscapeMarkup()} is {Sizes.Format(file.Length)}, " +
+                                   $"over the {thresholdMb} MB limit[/]");
+        }
+
+        switch (Policy)
+        {
+            case "warn":
+                break;
+            case "exclude":
+                foreach (var file in largeFiles) file.Delete();
+                AnsiConsole.MarkupLine($"[yellow]Excluded {largeFiles.Count} large files.[/]");
+                break;
+            case "lfs":
+                await Lfs.WriteAttributes(versionPath,
+                    largeFiles.Select(x => "/" + Path.GetRelativePath(versionPath, x.FullName).Replace('\\', '/')));
+                AnsiConsole.MarkupLine($"[yellow]Tracking {largeFiles.Count} large files with LFS.[/]");
+                break;
+            case "fail":
+                throw new MbssException(ErrorKind.ContentCheck,
+                    $"{largeFiles.Count} files exceed the {thresholdMb} MB limit!");
+            default:
+                throw new MbssException(ErrorKind.Config, $"Unknown large file policy {Policy}!");
+        }
+    }
+}
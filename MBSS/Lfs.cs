@@ -0,0 +1,143 @@
+﻿using System.Net.Http.Headers;
+using System.Text;
+using LibGit2Sharp;
+using Newtonsoft.Json;
+using Newtonsoft.Json.Linq;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal static class Lfs
+{
+    private const string MediaType = "application/vnd.git-lfs+json";
+
+    private static readonly List<(string Oid, long Size)> Pending = new();
+    private static FilterRegistration? _registration;
+
+    public static string[] Patterns => (Environment.GetEnvironmentVariable("MBSS_LFS") ?? "")
+        .Split(';', StringSplitOptions.RemoveEmptyEntries | StringSplitOptions.TrimEntries);
+
+    public static bool Enabled => Patterns.Length > 0 || LargeFiles.Policy == "lfs";
+
+    public static void Register()
+    {
+        if (!Enabled || _registration != null) return;
+        _registration = GlobalSettings.RegisterFilter(new LfsFilter());
+    }
+
+    public static async Task WriteAttributes(string versionPath, IEnumerable<string> patterns)
+    {
+        // Attribute patterns are split on whitespace, git-lfs escapes it the same way
+        var lines = patterns.Select(x => $"{x.Replace(" ", "[[:space:]]")} filter=lfs diff=lfs merge=lfs -text");
+        await File.AppendAllLinesAsync(Path.Combine(versionPath, ".gitattributes"), lines);
+    }
+
+    public static string ObjectPath(string root, string oid)
+    {
+        return Path.Combine(root, ".git", "lfs", "objects", oid[..2], oid[2..4], oid);
+    }
+
+    public static void Track(string oid, long size)
+    {
+        lock (Pending) Pending.Add((oid, size));
+    }
+
+    // Objects stay tracked until the push went through, a retry after a failed push has to upload them again
+    public static async Task Upload(string remoteUrl)
+    {
+        List<(string Oid, long Size)> objects;
+        lock (Pending) objects = Pending.Distinct().ToList();
+
+        if (objects.Count == 0) return;
+        if (!remoteUrl.StartsWith("https://"))
+            throw new MbssException(ErrorKind.Config,
+                $"LFS uploads require an HTTPS remote, {remoteUrl} is not supported!");
+
+        AnsiConsole.MarkupLine($"[yellow]Uploading {objects.Count} LFS objects...[/]");
+
+        using var client = Http.CreateClient();
+        var batchUrl = remoteUrl.TrimEnd('/');
+        if (!batchUrl.EndsWith(".git")) batchUrl += ".git";
+        batchUrl += "/info/lfs/objects/batch";
+
+        var batch = JObject.Parse(await Send(client, HttpMethod.Post, batchUrl, JsonConvert.SerializeObject(new
+        {
+            operation = "upload",
+            transfers = new[] { "basic" },
+            objects = objects.Select(x => new { oid = x.Oid, size = x.Size })
+        })));
+
+        foreach (var obj in batch["objects"]?.Children<JObject>() ?? Enumerable.Empty<JObject>())
+        {
+            var oid = obj.Value<string>("oid")!;
+            if (obj["error"] != null)
+                throw new MbssException(ErrorKind.GitPush,
+                    $"LFS server rejected object {oid}: {obj["error"]?["message"]}");
+
+            // Objects without actions already exist on the server
+            var upload = obj["actions"]?["upload"];
+            if (upload == null) continue;
+
+            await using var file = File.OpenRead(ObjectPath(Workspace.RepositoryPath, oid));
+            var put = new HttpRequestMessage(HttpMethod.Put, upload.Value<string>("href"))
+            {
+                Content = new StreamContent(file)
+            };
+            put.Content.Headers.ContentType = new MediaTypeHeaderValue("application/octet-stream");
+            AddHeaders(put, upload["header"]);
+
+            var res = await client.SendAsync(put);
+            if (!res.IsSuccessStatusCode)
+                throw new MbssException(ErrorKind.GitPush,
+                    $"Failed to upload LFS object {oid} ({(int)res.StatusCode})!");
+
+            var verify = obj["actions"]?["verify"];
+            if (verify == null) continue;
+
+            await Send(client, HttpMethod.Post, verify.Value<string>("href")!,
+                JsonConvert.SerializeObject(new { oid, size = obj.Value<long>("size") }), verify["header"]);
+        }
+
+        AnsiConsole.MarkupLine("[green]LFS objects uploaded![/]");
+    }
+
+    public static void Pushed()
+    {
+        lock (Pending) Pending.Clear();
+    }
+
+    private static async Task<string> Send(HttpClient client, HttpMethod method, string url, string body,
+        JToken? headers = null)
+    {
+        var req = new HttpRequestMessage(method, url)
+        {
+            Content = new StringContent(body, Encoding.UTF8, MediaType)
+        };
+        req.Headers.Accept.Add(new MediaTypeWithQualityHeaderValue(MediaType));
+
+        // The batch endpoint authenticates like git itself, action hrefs bring their own headers
+        if (headers == null)
+        {
+            var auth = $"{Environment.GetEnvironmentVariable("GIT_AUTHOR_NAME")}:" +
+                       Environment.GetEnvironmentVariable("GITHUB_TOKEN");
+            req.Headers.Authorization =
+                new AuthenticationHeaderValue("Basic", Convert.ToBase64String(Encoding.UTF8.GetBytes(auth)));
+        }
+        else
+        {
+            AddHeaders(req, headers);
+        }
+
+        var res = await client.SendAsync(req);
+        if (!res.IsSuccessStatusCode)
+            throw new MbssException(ErrorKind.GitPush, $"LFS request to {url} failed ({(int)res.StatusCode})!");
+
+        return await res.Content.ReadAsStringAsync();
+    }
+
+    private static void AddHeaders(HttpRequestMessage req, JToken? headers)
+    {
+        if (headers is not JObject obj) return;
+        foreach (var (name, value) in obj) req.Headers.TryAddWithoutValidation(name, value?.ToString());
+    }
+}
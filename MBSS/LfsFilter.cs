@@ -0,0 +1,85 @@
+﻿using System.Security.Cryptography;
+using System.Text;
+using LibGit2Sharp;
+
+namespace MBSS;
+
+internal class LfsFilter : Filter
+{
+    private const string PointerVersion = "version https://git-lfs.github.com/spec/v1";
+
+    private readonly Dictionary<string, (FilterMode Mode, string TempPath)> _pending = new();
+
+    public LfsFilter() : base("lfs", new[] { new FilterAttributeEntry("lfs") })
+    {
+    }
+
+    protected override void Create(string path, string root, FilterMode mode)
+    {
+        var tempDirectory = Path.Combine(root, ".git", "lfs", "tmp");
+        Directory.CreateDirectory(tempDirectory);
+        _pending[path] = (mode, Path.Combine(tempDirectory, Guid.NewGuid().ToString("N")));
+    }
+
+    // libgit2 streams file contents in chunks, so they are collected until Complete is called
+    protected override void Clean(string path, string root, Stream input, Stream output)
+    {
+        Append(path, input);
+    }
+
+    protected override void Smudge(string path, string root, Stream input, Stream output)
+    {
+        Append(path, input);
+    }
+
+    protected override void Complete(string path, string root, Stream output)
+    {
+        var (mode, tempPath) = _pending[path];
+        _pending.Remove(path);
+        if (!File.Exists(tempPath)) File.Create(tempPath).Dispose();
+
+        try
+        {
+            if (mode == FilterMode.Clean) WritePointer(root, tempPath, output);
+            else WriteObject(root, tempPath, output);
+        }
+        finally
+        {
+            if (File.Exists(tempPath)) File.Delete(tempPath);
+        }
+    }
+
+    private void Append(string path, Stream input)
+    {
+        using var temp = new FileStream(_pending[path].TempPath, FileMode.Append);
+        input.CopyTo(temp);
+    }
+
+    private static void WritePointer(string root, string tempPath, Stream output)
+    {
+        string oid;
+        using (var temp = File.OpenRead(tempPath)) oid = Convert.ToHexString(SHA256.HashData(temp)).ToLowerInvariant();
+
+        var size = new FileInfo(tempPath).Length;
+        var objectPath = Lfs.ObjectPath(root, oid);
+        if (!File.Exists(objectPath))
+        {
+            Directory.CreateDirectory(Path.GetDirectoryName(objectPath)!);
+            File.Move(tempPath, objectPath);
+        }
+
+        Lfs.Track(oid, size);
+        output.Write(Encoding.UTF8.GetBytes($"{PointerVersion}\noid sha256:{oid}\nsize {size}\n"));
+    }
+
+    private static void WriteObject(string root, string tempPath, Stream output)
+    {
+        var pointer = File.ReadAllText(tempPath);
+        var oid = pointer.Split('\n').FirstOrDefault(x => x.StartsWith("oid sha256:"))?["oid sha256:".Length..];
+        var objectPath = oid == null ? null : Lfs.ObjectPath(root, oid);
+
+        // Objects that were never fetched are left as pointers, like git-lfs does
+        using var source = File.OpenRead(objectPath != null && File.Exists(objectPath) ? objectPath : tempPath);
+        source.CopyTo(output);
+    }
+}
@@ -0,0 +1,103 @@
+﻿using System.Text;
+using System.Text.RegularExpressions;
+
+namespace MBSS;
+
+internal class LogFile : TextWriter
+{
+    private static readonly Regex EscapeRegex = new(@"\x1b\[[0-9;?]*[A-Za-z]");
+
+    private readonly TextWriter _console;
+    private readonly string _basePath;
+    private readonly bool _daily;
+    private readonly long _maxBytes;
+    private readonly int _retention;
+    private readonly StringBuilder _line = new();
+
+    private StreamWriter? _file;
+    private string? _filePath;
+
+    private LogFile(TextWriter console, string basePath)
+    {
+        _console = console;
+        _basePath = Path.GetFullPath(basePath);
+        _daily = Environment.GetEnvironmentVariable("MBSS_LOG_ROTATION")?.ToLowerInvariant() != "size";
+        _maxBytes = (long.TryParse(Environment.GetEnvironmentVariable("MBSS_LOG_MAX_SIZE_MB"), out var mb) ? mb : 10) *
+                    1024 * 1024;
+        _retention = int.TryParse(Environment.GetEnvironmentVariable("MBSS_LOG_RETENTION"), out var count) ? count : 7;
+    }
+
+    public override Encoding Encoding => _console.Encoding;
+
+    public static void Init()
+    {
+        var path = Environment.GetEnvironmentVariable("MBSS_LOG_FILE");
+        if (string.IsNullOrEmpty(path)) return;
+
+        // Progress bars are turned off since every redraw would end up in the file
+        ConsoleTap.Attach(writer => new LogFile(writer, path), false);
+    }
+
+    public override void Write(char value)
+    {
+        _console.Write(value);
+        if (value == '\r') return;
+        if (value != '\n')
+        {
+            _line.Append(value);
+            return;
+        }
+
+        var line = EscapeRegex.Replace(_line.ToString(), string.Empty);
+        _line.Clear();
+
+        lock (_line)
+        {
+            var file = Open();
+            file.WriteLine($"{DateTimeOffset.Now:yyyy-MM-dd HH:mm:ss} {line}");
+            file.Flush();
+        }
+    }
+
+    public override void Flush()
+    {
+        _console.Flush();
+    }
+
+    protected override void Dispose(bool disposing)
+    {
+        if (disposing) _file?.Dispose();
+        base.Dispose(disposing);
+    }
+
+    private StreamWriter Open()
+    {
+        var directory = Path.GetDirectoryName(_basePath)!;
+        var name = Path.GetFileNameWithoutExtension(_basePath);
+        var extension = Path.GetExtension(_basePath);
+
+        var path = _daily ? Path.Combine(directory, $"{name}.{DateTime.Now:yyyy-MM-dd}{extension}") : _basePath;
+        if (!_daily && _file != null && _file.BaseStream.Length >= _maxBytes)
+        {
+            _file.Dispose();
+            _file = null;
+            File.Move(_basePath, Path.Combine(directory, $"{name}.{DateTime.Now:yyyyMMdd-HHmmss}{extension}"), true);
+        }
+
+        if (_file != null && path == _filePath) return _file;
+
+        _file?.Dispose();
+        Directory.CreateDirectory(directory);
+        _file = new StreamWriter(path, true);
+        _filePath = path;
+
+        // Rotated files sort by their timestamps, only the newest ones are kept
+        var rotated = Directory.EnumerateFiles(directory, $"{name}.*{extension}")
+            .Where(x => x != path)
+            .OrderByDescending(x => x)
+            .Skip(_daily ? _retention - 1 : _retention);
+        foreach (var old in rotated) File.Delete(old);
+
+        return _file;
+    }
+}
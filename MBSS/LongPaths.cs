@@ -0,0 +1,28 @@
+﻿using Microsoft.Win32;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal static class LongPaths
+{
+    // .NET opts itself out of MAX_PATH, raw Win32 calls and the tools we run only do when the system allows it
+    public static string Extended(string path)
+    {
+        if (!OperatingSystem.IsWindows()) return path;
+
+        var full = Path.GetFullPath(path);
+        if (full.StartsWith(@"\\?\")) return full;
+        return full.StartsWith(@"\\") ? @"\\?\UNC\" + full[2..] : @"\\?\" + full;
+    }
+
+    public static void Check()
+    {
+        if (!OperatingSystem.IsWindows()) return;
+
+        using var key = Registry.LocalMachine.OpenSubKey(@"SYSTEM\CurrentControlSet\Control\FileSystem");
+        if (key?.GetValue("LongPathsEnabled") is 1) return;
+
+        AnsiConsole.MarkupLine("[yellow]Long paths are not enabled, stripping deep depot directories may fail. " +
+                               @"Set HKLM\SYSTEM\CurrentControlSet\Control\FileSystem\LongPathsEnabled to 1.[/]");
+    }
+}
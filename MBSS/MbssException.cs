@@ -0,0 +1,51 @@
+﻿using LibGit2Sharp;
+
+namespace MBSS;
+
+internal enum ErrorKind
+{
+    Other,
+    Config,
+    SteamAuth,
+    ManifestNotFound,
+    Download,
+    ToolDownload,
+    StripFailure,
+    GitPush,
+    RateLimited,
+    DiskSpace,
+    ContentCheck,
+    Hook,
+    Repository,
+    Publish
+}
+
+internal class MbssException : Exception
+{
+    public MbssException(ErrorKind kind, string message, Exception? inner = null) : base(message, inner)
+    {
+        Kind = kind;
+    }
+
+    public ErrorKind Kind { get; }
+
+    public static ErrorKind KindOf(Exception e)
+    {
+        return e switch
+        {
+            MbssException mbss => mbss.Kind,
+            TimeoutException => ErrorKind.Download,
+            LibGit2SharpException => ErrorKind.GitPush,
+            _ => ErrorKind.Other
+        };
+    }
+
+    // Configuration, credentials, a full disk, rejected content or a repository that needs sorting out take a human,
+    // running again won't help. Failed publishing is redone with MBSS publish, the version itself went through
+    public static bool IsRetryable(Exception e)
+    {
+        return KindOf(e) is not (ErrorKind.Config or ErrorKind.SteamAuth or ErrorKind.ManifestNotFound
+            or ErrorKind.DiskSpace or ErrorKind.ContentCheck or ErrorKind.Hook or ErrorKind.Repository
+            or ErrorKind.Publish);
+    }
+}
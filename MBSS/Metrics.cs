@@ -0,0 +1,116 @@
+﻿using System.Globalization;
+using System.Net.Http.Headers;
+using System.Text;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal static class Metrics
+{
+    private static readonly double[] Buckets = { 10, 60, 300, 900, 1800, 3600, 7200 };
+
+    private static readonly Dictionary<string, long> VersionCounts = new();
+    private static readonly Dictionary<string, (long[] Buckets, double Sum, long Count)> StageDurations = new();
+    private static long _downloadedBytes;
+    private static long _runs;
+    private static long _failedRuns;
+    private static long _lastRun;
+    private static long _lastSuccess;
+
+    public static void Record(IReadOnlyCollection<VersionReport> versions, bool failed)
+    {
+        lock (VersionCounts)
+        {
+            foreach (var version in versions)
+            {
+                VersionCounts[version.Status] = VersionCounts.GetValueOrDefault(version.Status) + 1;
+                _downloadedBytes += version.DownloadedBytes;
+
+                foreach (var (stage, seconds) in version.Stages)
+                {
+                    var (buckets, sum, count) = StageDurations.GetValueOrDefault(stage,
+                        (new long[Buckets.Length], 0d, 0L));
+                    for (var i = 0; i < Buckets.Length; i++)
+                        if (seconds <= Buckets[i]) buckets[i]++;
+
+                    StageDurations[stage] = (buckets, sum + seconds, count + 1);
+                }
+            }
+
+            var now = DateTimeOffset.UtcNow.ToUnixTimeSeconds();
+            _runs++;
+            _lastRun = now;
+            if (failed) _failedRuns++;
+            else _lastSuccess = now;
+        }
+    }
+
+    public static string Render()
+    {
+        var sb = new StringBuilder();
+        lock (VersionCounts)
+        {
+            sb.AppendLine("# HELP mbss_versions_total Versions handled by MBSS, by outcome.");
+            sb.AppendLine("# TYPE mbss_versions_total counter");
+            foreach (var (status, count) in VersionCounts)
+                sb.AppendLine($"mbss_versions_total{{status=\"{status}\"}} {count}");
+
+            sb.AppendLine("# HELP mbss_downloaded_bytes_total Bytes downloaded from Steam.");
+            sb.AppendLine("# TYPE mbss_downloaded_bytes_total counter");
+            sb.AppendLine($"mbss_downloaded_bytes_total {_downloadedBytes}");
+
+            sb.AppendLine("# HELP mbss_stage_duration_seconds Time spent in each pipeline stage.");
+            sb.AppendLine("# TYPE mbss_stage_duration_seconds histogram");
+            foreach (var (stage, (buckets, sum, count)) in StageDurations)
+            {
+                for (var i = 0; i < Buckets.Length; i++)
+                    sb.AppendLine($"mbss_stage_duration_seconds_bucket{{stage=\"{stage}\",le=\"{Buckets[i]}\"}} " +
+                                  buckets[i]);
+
+                sb.AppendLine($"mbss_stage_duration_seconds_bucket{{stage=\"{stage}\",le=\"+Inf\"}} {count}");
+                sb.AppendLine($"mbss_stage_duration_seconds_sum{{stage=\"{stage}\"}} " +
+                              sum.ToString(CultureInfo.InvariantCulture));
+                sb.AppendLine($"mbss_stage_duration_seconds_count{{stage=\"{stage}\"}} {count}");
+            }
+
+            sb.AppendLine("# HELP mbss_runs_total Runs completed, failed ones are also counted separately.");
+            sb.AppendLine("# TYPE mbss_runs_total counter");
+            sb.AppendLine($"mbss_runs_total {_runs}");
+            sb.AppendLine("# TYPE mbss_failed_runs_total counter");
+            sb.AppendLine($"mbss_failed_runs_total {_failedRuns}");
+
+            // Alerting on the age of the last success catches mirrors that silently stopped running
+            sb.AppendLine("# TYPE mbss_last_run_timestamp_seconds gauge");
+            sb.AppendLine($"mbss_last_run_timestamp_seconds {_lastRun}");
+            sb.AppendLine("# TYPE mbss_last_success_timestamp_seconds gauge");
+            sb.AppendLine($"mbss_last_success_timestamp_seconds {_lastSuccess}");
+        }
+
+        return sb.ToString();
+    }
+
+    public static async Task Push()
+    {
+        var url = Environment.GetEnvironmentVariable("MBSS_PUSHGATEWAY_URL");
+        if (string.IsNullOrEmpty(url) || Options.Offline) return;
+
+        using var client = Http.CreateClient();
+        var content = new StringContent(Render(), Encoding.UTF8);
+        content.Headers.ContentType = new MediaTypeHeaderValue("text/plain")
+        {
+            Parameters = { new NameValueHeaderValue("version", "0.0.4") }
+        };
+
+        try
+        {
+            var res = await client.PutAsync($"{url.TrimEnd('/')}/metrics/job/mbss", content);
+            if (!res.IsSuccessStatusCode)
+                AnsiConsole.MarkupLine($"[yellow]Pushgateway rejected the metrics ({(int)res.StatusCode}).[/]");
+        }
+        catch (HttpRequestException e)
+        {
+            // Metrics are best effort, they shouldn't turn a successful run into a failed one
+            AnsiConsole.MarkupLine($"[yellow]Failed to push metrics: {e.Message.EscapeMarkup()}[/]");
+        }
+    }
+}
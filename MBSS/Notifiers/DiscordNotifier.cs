@@ -0,0 +1,26 @@
+﻿using System.Text;
+using Newtonsoft.Json;
+
+namespace MBSS.Notifiers;
+
+internal class DiscordNotifier : INotifier
+{
+    private const string DefaultTemplate = "Beat Saber **{version}** is now available ({files} files, {size})\n{url}";
+
+    private readonly string _webhookUrl;
+    private readonly string _template;
+
+    public DiscordNotifier(string webhookUrl, string? template)
+    {
+        _webhookUrl = webhookUrl;
+        _template = template ?? DefaultTemplate;
+    }
+
+    public async Task Send(HttpClient client, NotificationEvent notification)
+    {
+        var payload = JsonConvert.SerializeObject(new { content = notification.Render(_template) });
+        var res = await client.PostAsync(_webhookUrl, new StringContent(payload, Encoding.UTF8, "application/json"));
+        if (!res.IsSuccessStatusCode)
+            throw new MbssException(ErrorKind.Publish, $"Discord webhook returned {(int)res.StatusCode}!");
+    }
+}
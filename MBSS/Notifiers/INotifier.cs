@@ -0,0 +1,31 @@
+﻿namespace MBSS.Notifiers;
+
+internal interface INotifier
+{
+    Task Send(HttpClient client, NotificationEvent notification);
+}
+
+internal record NotificationEvent(string Type, string Message, PublishedVersion? Published = null)
+{
+    public const string RunStarted = "run_started";
+    public const string VersionPublished = "version_published";
+    public const string RunFailed = "run_failed";
+
+    public string Render(string? template)
+    {
+        return template != null && Published != null ? Published.Render(template.Replace("\\n", "\n")) : Message;
+    }
+}
+
+internal record PublishedVersion(BeatSaberVersion Version, string Url, int Files, long Size)
+{
+    public string Render(string template)
+    {
+        return template
+            .Replace("{version}", Version.Version)
+            .Replace("{manifest}", Version.Manifest)
+            .Replace("{url}", Url)
+            .Replace("{files}", Files.ToString())
+            .Replace("{size}", Sizes.Format(Size));
+    }
+}
@@ -0,0 +1,40 @@
+﻿using System.Net.Http.Headers;
+using System.Text;
+using Newtonsoft.Json;
+
+namespace MBSS.Notifiers;
+
+internal class MatrixNotifier : INotifier
+{
+    private const string DefaultTemplate = "Beat Saber {version} is now available ({files} files, {size}): {url}";
+
+    private readonly string _homeserver;
+    private readonly string _roomId;
+    private readonly string _token;
+    private readonly string _template;
+
+    public MatrixNotifier(string homeserver, string roomId, string token, string? template)
+    {
+        _homeserver = homeserver.TrimEnd('/');
+        _roomId = roomId;
+        _token = token;
+        _template = template ?? DefaultTemplate;
+    }
+
+    public async Task Send(HttpClient client, NotificationEvent notification)
+    {
+        var url = $"{_homeserver}/_matrix/client/v3/rooms/{Uri.EscapeDataString(_roomId)}/send/m.room.message/" +
+                  Guid.NewGuid().ToString("N");
+        var payload = JsonConvert.SerializeObject(new { msgtype = "m.text", body = notification.Render(_template) });
+
+        var req = new HttpRequestMessage(HttpMethod.Put, url)
+        {
+            Content = new StringContent(payload, Encoding.UTF8, "application/json")
+        };
+        req.Headers.Authorization = new AuthenticationHeaderValue("Bearer", _token);
+
+        var res = await client.SendAsync(req);
+        if (!res.IsSuccessStatusCode)
+            throw new MbssException(ErrorKind.Publish, $"Matrix homeserver returned {(int)res.StatusCode}!");
+    }
+}
@@ -0,0 +1,86 @@
+﻿using LibGit2Sharp;
+using Spectre.Console;
+
+namespace MBSS.Notifiers;
+
+internal static class Notifications
+{
+    public static List<INotifier> FromEnvironment()
+    {
+        var notifiers = new List<INotifier>();
+
+        var discord = Environment.GetEnvironmentVariable("MBSS_DISCORD_WEBHOOK_URL");
+        if (!string.IsNullOrEmpty(discord))
+            notifiers.Add(new DiscordNotifier(discord, Environment.GetEnvironmentVariable("MBSS_DISCORD_TEMPLATE")));
+
+        var slack = Environment.GetEnvironmentVariable("MBSS_SLACK_WEBHOOK_URL");
+        if (!string.IsNullOrEmpty(slack))
+            notifiers.Add(new SlackNotifier(slack, Environment.GetEnvironmentVariable("MBSS_SLACK_TEMPLATE")));
+
+        var homeserver = Environment.GetEnvironmentVariable("MBSS_MATRIX_HOMESERVER");
+        var room = Environment.GetEnvironmentVariable("MBSS_MATRIX_ROOM_ID");
+        var token = Environment.GetEnvironmentVariable("MBSS_MATRIX_TOKEN");
+        if (!string.IsNullOrEmpty(homeserver) && !string.IsNullOrEmpty(room) && !string.IsNullOrEmpty(token))
+            notifiers.Add(new MatrixNotifier(homeserver, room, token,
+                Environment.GetEnvironmentVariable("MBSS_MATRIX_TEMPLATE")));
+
+        var webhook = Environment.GetEnvironmentVariable("MBSS_NOTIFY_WEBHOOK_URL");
+        if (!string.IsNullOrEmpty(webhook)) notifiers.Add(new WebhookNotifier(webhook));
+
+        return notifiers;
+    }
+
+    public static async Task RunStarted(List<BeatSaberVersion> pending)
+    {
+        if (pending.Count == 0) return;
+
+        var versions = string.Join(", ", pending.Select(x => x.Version));
+        await Send(new NotificationEvent(NotificationEvent.RunStarted,
+            $"MBSS started processing {pending.Count} new versions: {versions}"));
+    }
+
+    public static async Task VersionPublished(BeatSaberVersion version, string versionPath)
+    {
+        string url;
+        using (var repo = new Repository(Workspace.RepositoryPath))
+        {
+            var remote = repo.Network.Remotes["origin"];
+            if (remote == null || Options.Offline) return; // Nothing was published
+            url = VersionTree.WebUrl(remote.Url, version.Version);
+        }
+
+        var files = Directory.EnumerateFiles(versionPath, "*", SearchOption.AllDirectories).Count();
+        var published = new PublishedVersion(version, url, files, Sizes.OfDirectory(versionPath));
+        await Send(new NotificationEvent(NotificationEvent.VersionPublished,
+            $"Beat Saber {version.Version} is now available: {url}", published));
+    }
+
+    public static async Task RunFailed(Exception? exception)
+    {
+        var failed = RunReport.Versions.Where(x => x.Status == "failed").Select(x => $"{x.Version}: {x.Error}");
+        var message = exception != null
+            ? $"MBSS run failed: {exception.Message}"
+            : $"MBSS run failed for {string.Join("; ", failed)}";
+        await Send(new NotificationEvent(NotificationEvent.RunFailed, Secrets.Redact(message)));
+    }
+
+    private static async Task Send(NotificationEvent notification)
+    {
+        var notifiers = FromEnvironment();
+        if (notifiers.Count == 0 || Options.Offline) return;
+
+        using var client = Http.CreateClient();
+        foreach (var notifier in notifiers)
+        {
+            // A broken webhook shouldn't fail a run, least of all one that already pushed something
+            try
+            {
+                await notifier.Send(client, notification);
+            }
+            catch (Exception e)
+            {
+                AnsiConsole.MarkupLine($"[yellow]Failed to send notification: {e.Message.EscapeMarkup()}[/]");
+            }
+        }
+    }
+}
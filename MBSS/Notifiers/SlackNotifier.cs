@@ -0,0 +1,26 @@
+﻿using System.Text;
+using Newtonsoft.Json;
+
+namespace MBSS.Notifiers;
+
+internal class SlackNotifier : INotifier
+{
+    private const string DefaultTemplate = "Beat Saber *{version}* is now available ({files} files, {size})\n<{url}>";
+
+    private readonly string _webhookUrl;
+    private readonly string _template;
+
+    public SlackNotifier(string webhookUrl, string? template)
+    {
+        _webhookUrl = webhookUrl;
+        _template = template ?? DefaultTemplate;
+    }
+
+    public async Task Send(HttpClient client, NotificationEvent notification)
+    {
+        var payload = JsonConvert.SerializeObject(new { text = notification.Render(_template) });
+        var res = await client.PostAsync(_webhookUrl, new StringContent(payload, Encoding.UTF8, "application/json"));
+        if (!res.IsSuccessStatusCode)
+            throw new MbssException(ErrorKind.Publish, $"Slack webhook returned {(int)res.StatusCode}!");
+    }
+}
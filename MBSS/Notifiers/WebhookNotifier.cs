@@ -0,0 +1,33 @@
+﻿using System.Text;
+using Newtonsoft.Json;
+
+namespace MBSS.Notifiers;
+
+internal class WebhookNotifier : INotifier
+{
+    private readonly string _url;
+
+    public WebhookNotifier(string url)
+    {
+        _url = url;
+    }
+
+    public async Task Send(HttpClient client, NotificationEvent notification)
+    {
+        var published = notification.Published;
+        var payload = JsonConvert.SerializeObject(new
+        {
+            @event = notification.Type,
+            message = notification.Message,
+            version = published?.Version.Version,
+            manifest = published?.Version.Manifest,
+            url = published?.Url,
+            files = published?.Files,
+            size = published?.Size
+        }, new JsonSerializerSettings { NullValueHandling = NullValueHandling.Ignore });
+
+        var res = await client.PostAsync(_url, new StringContent(payload, Encoding.UTF8, "application/json"));
+        if (!res.IsSuccessStatusCode)
+            throw new MbssException(ErrorKind.Publish, $"Webhook returned {(int)res.StatusCode}!");
+    }
+}
@@ -0,0 +1,64 @@
+﻿namespace MBSS;
+
+internal static class Options
+{
+    private static readonly HashSet<string> Flags = new();
+    private static readonly Dictionary<string, string> Values = new();
+    private static readonly HashSet<string> ValueOptions = new()
+    {
+        "--version", "--path", "--out", "--format", "--assets-dir", "--report", "--progress", "--log-format",
+        "--interval", "--ci", "--from-pattern", "--to-pattern",
+        "--dest", "--include", "--platform", "--store"
+    };
+
+    public static string? Command { get; private set; }
+    public static string[] CommandArgs { get; private set; } = Array.Empty<string>();
+
+    public static bool Reset => Flags.Contains("--reset");
+    public static bool Watch => Flags.Contains("--watch");
+    public static bool NoProcess => Flags.Contains("--no-process");
+    public static bool SkipPreflight => Flags.Contains("--skip-preflight");
+    public static bool Offline => Flags.Contains("--offline");
+    public static bool KeepGoing => Flags.Contains("--keep-going");
+    public static bool ReplaceAssets => Flags.Contains("--replace-assets");
+    public static bool DryRun => Flags.Contains("--dry-run");
+    public static bool Verbose => Flags.Contains("--verbose");
+
+    public static string? AssetsDirectory =>
+        Value("--assets-dir") ?? Environment.GetEnvironmentVariable("MBSS_ASSETS_DIR");
+
+    public static void Parse(string[] args)
+    {
+        var positionals = new List<string>();
+        for (var i = 0; i < args.Length; i++)
+        {
+            var arg = args[i];
+            if (!arg.StartsWith("--"))
+            {
+                positionals.Add(arg);
+                continue;
+            }
+
+            var split = arg.IndexOf('=');
+            if (split != -1)
+                Values[arg[..split]] = arg[(split + 1)..];
+            else if (ValueOptions.Contains(arg) && i + 1 < args.Length)
+                Values[arg] = args[++i];
+            else
+                Flags.Add(arg);
+        }
+
+        Command = positionals.FirstOrDefault();
+        CommandArgs = positionals.Skip(1).ToArray();
+    }
+
+    public static bool Has(string flag)
+    {
+        return Flags.Contains(flag);
+    }
+
+    public static string? Value(string option)
+    {
+        return Values.TryGetValue(option, out var value) ? value : null;
+    }
+}
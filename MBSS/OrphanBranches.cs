@@ -0,0 +1,76 @@
+﻿using LibGit2Sharp;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal static class OrphanBranches
+{
+    // Chained versions live only on main, orphan mode also gives every version a parentless branch of its own
+    public static bool Enabled =>
+        Environment.GetEnvironmentVariable("MBSS_COMMIT_MODE")?.ToLowerInvariant() == "orphan";
+
+    private static string Template =>
+        Environment.GetEnvironmentVariable("MBSS_BRANCH_TEMPLATE") ?? "version/{version}";
+
+    // Placeholders are {version} plus its {major}, {minor} and {patch} components, e.g. bs/{major}.{minor}.{patch}
+    public static string BranchName(string version, string? template = null)
+    {
+        template ??= Template;
+
+        // Anything coarser would put several versions on one branch, each push overwriting the one before
+        if (!template.Contains("{version}") && !new[] { "{major}", "{minor}", "{patch}" }.All(template.Contains))
+            throw new MbssException(ErrorKind.Config,
+                $"Branch template {template} has to contain {{version}} or all of {{major}}, {{minor}} and {{patch}}!");
+
+        var semver = SemVer.Parse(BeatSaberVersion.SplitKey(version).GameVersion);
+        var name = template
+            .Replace("{version}", SemVer.RefSafe(version))
+            .Replace("{major}", semver.Major.ToString())
+            .Replace("{minor}", semver.Minor.ToString())
+            .Replace("{patch}", semver.Patch.ToString());
+
+        if (!Reference.IsValidName($"refs/heads/{name}"))
+            throw new MbssException(ErrorKind.Config,
+                $"Branch template {template} produces the invalid branch name {name} for {version}!");
+        return name;
+    }
+
+    public static void Publish(BeatSaberVersion version, string message, Signature author)
+    {
+        using var repo = new Repository(Workspace.RepositoryPath);
+        var tree = VersionTree.Find(repo, version.Version) ??
+                   throw new MbssException(ErrorKind.Repository,
+                       $"Version {version.Version} is not committed on main!");
+
+        // Templates without {version} still map build metadata and other platforms onto the same branch
+        var branch = BranchName(version.Version);
+        foreach (var name in new[] { branch, $"origin/{branch}" })
+            if (repo.Branches[name] is { } existing && VersionOf(existing) is { } other && other != version.Version)
+                throw new MbssException(ErrorKind.Config,
+                    $"Branch {branch} already holds version {other}, refusing to overwrite it with " +
+                    $"{version.Version}! Add {{version}} to the branch template {Template} to tell them apart.");
+
+        var commit = repo.ObjectDatabase.CreateCommit(author, author, message, tree, Array.Empty<Commit>(), false);
+        repo.Refs.Add($"refs/heads/{branch}", commit.Id, true);
+        AnsiConsole.MarkupLine($"[green]Created orphan branch {branch}[/]");
+
+        var remote = repo.Network.Remotes["origin"];
+        if (remote == null || Options.Offline) return;
+
+        repo.Network.Push(remote, $"+refs/heads/{branch}",
+            new PushOptions { CredentialsProvider = Credentials.GitHandler });
+    }
+
+    // Whether the version has a branch of its own, and not just one that a coarse template shares with another
+    public static bool Exists(Repository repo, string version)
+    {
+        var branch = repo.Branches[BranchName(version)];
+        return branch != null && (VersionOf(branch) ?? version) == version;
+    }
+
+    // version.txt keeps the exact version, branches from before it was written don't have one
+    private static string? VersionOf(Branch branch)
+    {
+        return (branch.Tip?["version.txt"]?.Target as Blob)?.GetContentText().Trim();
+    }
+}
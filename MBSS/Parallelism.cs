@@ -0,0 +1,34 @@
+﻿using System.Security.Cryptography;
+using System.Text;
+using LibGit2Sharp;
+
+namespace MBSS;
+
+internal static class Parallelism
+{
+    public static int Degree =>
+        int.TryParse(Environment.GetEnvironmentVariable("MBSS_PARALLELISM"), out var degree) && degree > 0
+            ? degree
+            : Environment.ProcessorCount;
+
+    public static ParallelOptions Options => new() { MaxDegreeOfParallelism = Degree };
+
+    // libgit2 objects can't be shared between threads, but git's blob ids are plain SHA-1 and can be computed anywhere
+    public static ObjectId[] HashBlobs(IReadOnlyList<string> files)
+    {
+        var ids = new ObjectId[files.Count];
+        Parallel.For(0, files.Count, Options, i =>
+        {
+            using var hash = IncrementalHash.CreateHash(HashAlgorithmName.SHA1);
+            using var stream = Workspace.OpenBlobContent(files[i]);
+            hash.AppendData(Encoding.ASCII.GetBytes($"blob {stream.Length}\0"));
+
+            var buffer = new byte[81920];
+            int read;
+            while ((read = stream.Read(buffer)) > 0) hash.AppendData(buffer, 0, read);
+            ids[i] = new ObjectId(hash.GetHashAndReset());
+        });
+
+        return ids;
+    }
+}
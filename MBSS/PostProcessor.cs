@@ -0,0 +1,44 @@
+﻿using Spectre.Console;
+
+namespace MBSS;
+
+internal static class PostProcessor
+{
+    private static readonly Dictionary<string, Action<string>> BuiltIns = new()
+    {
+        ["filter"] = path => FileFilter.FromEnvironment().Apply(path),
+        ["remove-pdbs"] = path =>
+        {
+            foreach (var pdb in Directory.EnumerateFiles(path, "*.pdb", SearchOption.AllDirectories).ToList())
+                File.Delete(pdb);
+        }
+    };
+
+    public static async Task Run(BeatSaberVersion version, string versionPath)
+    {
+        var steps = Environment.GetEnvironmentVariable("MBSS_POST_PROCESS")
+            ?.Split(';', StringSplitOptions.RemoveEmptyEntries | StringSplitOptions.TrimEntries)
+            ?? Array.Empty<string>();
+
+        foreach (var step in steps)
+        {
+            AnsiConsole.MarkupLine($"[yellow]Running post-process step {step.EscapeMarkup()}...[/]");
+
+            if (step.StartsWith("builtin:"))
+            {
+                var name = step["builtin:".Length..];
+                if (!BuiltIns.TryGetValue(name, out var builtIn))
+                    throw new MbssException(ErrorKind.Config, $"Unknown built-in post-process step {name}!");
+
+                builtIn(versionPath);
+                continue;
+            }
+
+            var command = step.Replace("{version}", version.Version).Replace("{path}", versionPath);
+            var exitCode = await Shell.Run(command, Hooks.ContextEnvironment(version, versionPath));
+
+            if (exitCode != 0)
+                throw new MbssException(ErrorKind.Hook, $"Post-process step {step} failed with exit code {exitCode}!");
+        }
+    }
+}
@@ -0,0 +1,59 @@
+﻿using System.Net;
+using System.Text.RegularExpressions;
+using LibGit2Sharp;
+using MBSS.Sources;
+using Newtonsoft.Json.Linq;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal static class Preflight
+{
+    public static readonly Regex GitHubRemoteRegex =
+        new(@"github\.com[:/](?<owner>[^/]+)/(?<repo>[^/]+?)(\.git)?/?$", RegexOptions.IgnoreCase);
+
+    public static async Task Run(HttpClient client, IDepotSource depotSource)
+    {
+        AnsiConsole.MarkupLine("[yellow]Running preflight checks...[/]");
+
+        DiskSpace.CheckMargin(Directory.GetCurrentDirectory());
+        LongPaths.Check();
+        if (!Options.Offline) await CheckRemote(client);
+        await depotSource.Preflight();
+
+        AnsiConsole.MarkupLine("[green]Preflight checks passed![/]");
+    }
+
+    public static async Task CheckRemote(HttpClient client)
+    {
+        using var repo = new Repository(Workspace.RepositoryPath);
+        var remote = repo.Network.Remotes["origin"];
+        if (remote == null)
+        {
+            AnsiConsole.MarkupLine("[yellow]No origin remote configured, versions will not be pushed.[/]");
+            return;
+        }
+
+        try
+        {
+            Repository.ListRemoteReferences(remote.Url, Credentials.GitHandler).ToList();
+        }
+        catch (LibGit2SharpException e)
+        {
+            throw new MbssException(ErrorKind.GitPush, $"Failed to access remote {remote.Url}: {e.Message}");
+        }
+
+        var match = GitHubRemoteRegex.Match(remote.Url);
+        if (!match.Success) return;
+
+        var res = await GitHubApi.Get(client,
+            $"https://api.github.com/repos/{match.Groups["owner"].Value}/{match.Groups["repo"].Value}");
+        if (res.StatusCode != HttpStatusCode.OK)
+            throw new MbssException(ErrorKind.GitPush,
+                $"GITHUB_TOKEN cannot access {remote.Url} ({(int)res.StatusCode})!");
+
+        var repository = JObject.Parse(await res.Content.ReadAsStringAsync());
+        if (repository["permissions"]?["push"]?.Value<bool>() != true)
+            throw new MbssException(ErrorKind.GitPush, $"GITHUB_TOKEN does not have push access to {remote.Url}!");
+    }
+}
@@ -1,163 +1,637 @@
 ﻿using System.Diagnostics;
-using System.IO.Compression;
-using System.Net;
 using LibGit2Sharp;
+using MBSS.Commands;
+using MBSS.Notifiers;
+using MBSS.Publishing;
+using MBSS.Sources;
+using MBSS.Tools;
 using Newtonsoft.Json;
-using Newtonsoft.Json.Linq;
 using Spectre.Console;
 
 namespace MBSS;
 
-internal class BeatSaberVersion
-{
-    [JsonProperty("version")] public string Version { get; set; } = string.Empty;
-    [JsonProperty("manifest")] public string Manifest { get; set; } = string.Empty;
-}
-
 internal abstract class Program
 {
     public static async Task Main(string[] args)
     {
-        InitConsole();
+        Options.Parse(args);
+
+        // Setup is inside the try as well, broken configuration has to end with the config exit code too
+        try
+        {
+            if (File.Exists(".env")) await SetupDotEnv();
 
-        var client = new HttpClient();
-        client.DefaultRequestHeaders.Add("User-Agent", "MBSS");
+            Progress.Init();
+            JsonLog.Init();
+            LogFile.Init();
+            Telemetry.Init();
+            ErrorReporter.Init();
+            GitHubActions.Init();
+            ConsoleTap.Redact();
 
-        #region Arguments
+            // Scripts capture the output of these, the banner would only get in the way
+            if (Options.Command is not ("resolve" or "exists")) InitConsole();
 
-        if (args.Length > 0 && args[0] == "--reset")
+            #region Arguments
+
+            Workspace.Open();
+
+            if (Options.Reset)
+            {
+                if (!Workspace.IsManaged())
+                    throw new MbssException(ErrorKind.Config,
+                        "This does not look like an MBSS repository, refusing to reset! " +
+                        "Create an empty .mbss file if this is really where versions go.");
+
+                // Only this workspace's files, the tools directory is shared with other checkouts and the daemon
+                AnsiConsole.MarkupLine("[red]Resetting MBSS and deleting all files...[/]");
+                if (Directory.Exists("versions")) Directory.Delete("versions", true);
+                if (Directory.Exists("downloads")) Directory.Delete("downloads", true);
+            }
+
+            #endregion
+
+            var client = Http.CreateClient();
+
+            switch (Options.Command)
+            {
+                case null:
+                    await Run(client);
+                    break;
+                case "daemon":
+                    await DaemonCommand.Run(version => Run(client, version));
+                    break;
+                case "queue":
+                    QueueCommand.Run(Options.CommandArgs);
+                    break;
+                case "tools":
+                    await ToolsCommand.Run(client, Options.CommandArgs);
+                    break;
+                case "import":
+                    await Import(client);
+                    break;
+                case "publish":
+                    await PublishCommand.Run(Options.CommandArgs);
+                    break;
+                case "publish-site":
+                    await PublishSiteCommand.Run();
+                    break;
+                case "migrate":
+                    MigrateCommand.Run();
+                    break;
+                case "checkout":
+                    if (!await CheckoutCommand.Run(Options.CommandArgs)) Environment.ExitCode = ExitCodes.Failure;
+                    break;
+                case "resolve":
+                    if (!ResolveCommand.Run(Options.CommandArgs)) Environment.ExitCode = ExitCodes.Failure;
+                    break;
+                case "exists":
+                    if (!ExistsCommand.Run(Options.CommandArgs)) Environment.ExitCode = ExitCodes.Failure;
+                    break;
+                case "repair":
+                    await Repair(client);
+                    break;
+                case "cache":
+                    await CacheCommand.Run(Options.CommandArgs);
+                    break;
+                case "export":
+                    await ExportCommand.Run(Options.CommandArgs);
+                    break;
+                case "diff":
+                    DiffCommand.Run(Options.CommandArgs);
+                    break;
+                case "doctor":
+                    if (!await DoctorCommand.Run(client)) Environment.ExitCode = ExitCodes.Failure;
+                    break;
+                case "gc":
+                    await GitMaintenance.Run();
+                    break;
+                case "stats":
+                    StatsCommand.Run();
+                    break;
+                case "verify":
+                    if (!await VerifyCommand.Run(client, Options.CommandArgs))
+                        Environment.ExitCode = ExitCodes.Failure;
+                    break;
+                default:
+                    AnsiConsole.MarkupLine($"[red]Unknown command {Options.Command.EscapeMarkup()}![/]");
+                    Environment.ExitCode = ExitCodes.Config;
+                    break;
+            }
+        }
+        catch (Exception e)
+        {
+            // Version failures have already been annotated with the version they belong to
+            if (!RunReport.Failed) GitHubActions.Error(e.Message, "MBSS failed");
+            await ErrorReporter.Report(e);
+            await Notifications.RunFailed(e);
+
+            // Reported here instead of rethrown, an unhandled exception would hide the exit code CI relies on
+            AnsiConsole.MarkupLine($"[red]{e.Message.EscapeMarkup()}[/]");
+            if (Options.Verbose) AnsiConsole.WriteException(e);
+            Environment.ExitCode = ExitCodes.For(e);
+        }
+        finally
         {
-            AnsiConsole.MarkupLine("[red]Resetting MBSS and deleting all files...[/]");
-            if (Directory.Exists("versions")) Directory.Delete("versions", true);
-            if (Directory.Exists("downloads")) Directory.Delete("downloads", true);
-            if (Directory.Exists("bin")) Directory.Delete("bin", true);
+            // Buffered spans are lost unless the exporter is flushed before exiting
+            Telemetry.Shutdown();
         }
+    }
 
-        #endregion
+    private static async Task Run(HttpClient client, string? onlyVersion = null)
+    {
+        RunReport.Begin();
+        var depotSource = CreateDepotSource();
+        await Prepare(client, depotSource);
 
         #region Versions
 
-        if (!File.Exists("versions.json"))
+        var versionsSourceUrl = Environment.GetEnvironmentVariable("MBSS_VERSIONS_SOURCE_URL");
+        if (!File.Exists("versions.json") && string.IsNullOrEmpty(versionsSourceUrl))
+            throw new MbssException(ErrorKind.Config, "versions.json does not exist!");
+
+        var versions = File.Exists("versions.json") ? await LoadVersions() : new List<BeatSaberVersion>();
+
+        #endregion
+
+        #region Versions Sync
+
+        if (!string.IsNullOrEmpty(versionsSourceUrl) && !Options.Offline &&
+            await VersionSync.Merge(client, versionsSourceUrl, versions))
         {
-            AnsiConsole.MarkupLine("[red]versions.json does not exist![/]");
-            return;
+            await BeatSaberVersion.SaveAll(versions);
+            await CommitAndPush("chore: sync versions.json", Path.GetFullPath("versions.json"));
         }
 
-        var versions =
-            JsonConvert.DeserializeObject<List<BeatSaberVersion>>(await File.ReadAllTextAsync("versions.json"));
-        if (versions == null)
+        #endregion
+
+        #region Watcher
+
+        if (Options.Watch && !Options.Offline)
         {
-            AnsiConsole.MarkupLine("[red]Failed to parse versions.json![/]");
-            return;
+            var discovered = await SteamWatcher.Discover(versions);
+            if (discovered.Count > 0)
+            {
+                versions.AddRange(discovered);
+                await BeatSaberVersion.SaveAll(versions);
+                await CommitAndPush(
+                    $"chore: discovered {string.Join(", ", discovered.Select(x => $"v{x.Version}"))}",
+                    Path.GetFullPath("versions.json"));
+            }
+
+            if (Options.NoProcess) return;
         }
 
         #endregion
 
-        #region Environment Variables
+        if (onlyVersion != null)
+        {
+            versions = versions.Where(x => x.Version == onlyVersion).ToList();
+            if (versions.Count == 0)
+                AnsiConsole.MarkupLine($"[red]Version {onlyVersion.EscapeMarkup()} is not in versions.json![/]");
+        }
 
-        if (File.Exists(".env")) await SetupDotEnv();
+        await Notifications.RunStarted(versions.Where(x => !Workspace.VersionExists(x, VersionPath(x))).ToList());
 
-        var envs = new[] { "STEAM_USERNAME", "STEAM_PASSWORD", "GIT_AUTHOR_NAME", "GIT_AUTHOR_EMAIL", "GITHUB_TOKEN" };
-        foreach (var env in envs.Where(env => string.IsNullOrEmpty(Environment.GetEnvironmentVariable(env))))
+        var committed = 0;
+        foreach (var version in versions)
         {
-            AnsiConsole.MarkupLine($"[red]Environment variable {env} is not set![/]");
+            var report = RunReport.Start(version);
+            if (Workspace.VersionExists(version, VersionPath(version)))
+            {
+                AnsiConsole.MarkupLine($"[yellow]Version {version.Version} already exists, skipping...[/]");
+                report.Status = "skipped";
+                continue;
+            }
+
+            try
+            {
+                var changed = await ProcessVersion(depotSource, version);
+                report.Status = changed ? "committed" : "unchanged";
+                if (changed) committed++;
+                Progress.Emit("version_finished", report);
+            }
+            catch (Exception e)
+            {
+                report.Status = "failed";
+                report.Error = e.Message;
+                report.ErrorKind = MbssException.KindOf(e);
+                if (report.Log != null)
+                    AnsiConsole.MarkupLine($"[yellow]Tool output was saved to {report.Log.EscapeMarkup()}[/]");
+                Progress.Emit("version_failed", report);
+                GitHubActions.Error(e.Message, $"Version {version.Version} failed");
+                if (!Options.KeepGoing)
+                {
+                    await RunReport.Finish();
+                    throw;
+                }
+
+                AnsiConsole.MarkupLine($"[red]Version {version.Version} failed: {e.Message.EscapeMarkup()}[/]");
+            }
+        }
+
+        RunReport.End();
+
+        // Commits from offline or interrupted runs only exist locally until main is pushed again
+        using (var repo = new Repository(Workspace.RepositoryPath)) await PushMain(repo);
+
+        if (committed > 0 && GitMaintenance.AutoEnabled) await GitMaintenance.Run();
+
+        await RunReport.Finish();
+        if (!RunReport.Failed) return;
+
+        Environment.ExitCode = ExitCodes.For(RunReport.Versions);
+        await ErrorReporter.Report(null);
+        await Notifications.RunFailed(null);
+    }
+
+    private static async Task Import(HttpClient client)
+    {
+        var versionName = Options.Value("--version");
+        var gamePath = Options.Value("--path");
+        if (versionName == null || gamePath == null)
+            throw new MbssException(ErrorKind.Config,
+                "Usage: MBSS import --version <version> --path <game directory>");
+
+        var depotSource = new ImportSource(gamePath);
+        await Prepare(client, depotSource);
+
+        var versions = File.Exists("versions.json") ? await LoadVersions() : null;
+        var version = versions?.FirstOrDefault(x => x.Version == versionName) ??
+                      BeatSaberVersion.FromKey(versionName, "local");
+
+        if (Workspace.VersionExists(version, VersionPath(version)))
+        {
+            // Nothing was imported, scripts have to be able to tell that apart from a successful import
+            AnsiConsole.MarkupLine($"[red]Version {version.Version} already exists, aborting.[/]");
+            Environment.ExitCode = ExitCodes.Config;
             return;
         }
 
+        await ProcessVersion(depotSource, version, $"Imported from a local installation at {gamePath}.");
+    }
+
+    private static async Task Repair(HttpClient client)
+    {
+        var broken = await RepairCommand.FindBroken();
+        if (broken.Count == 0 || Options.DryRun) return;
+
+        foreach (var version in broken)
+        {
+            // The broken copy goes first, otherwise the run would see the version as already mirrored
+            AnsiConsole.MarkupLine($"[yellow]Rebuilding version {version.EscapeMarkup()}...[/]");
+            var versionPath = Path.GetFullPath(Path.Combine("versions", version));
+            if (Directory.Exists(versionPath)) Directory.Delete(versionPath, true);
+            await CommitAndPush($"chore: remove broken v{version}", versionPath);
+
+            await Run(client, version);
+        }
+    }
+
+    private static async Task Prepare(HttpClient client, IDepotSource depotSource)
+    {
+        #region Environment Variables
+
+        var envs = new List<string> { "GIT_AUTHOR_NAME", "GIT_AUTHOR_EMAIL" };
+        if (!Options.Offline) envs.Add("GITHUB_TOKEN");
+        if (depotSource.RequiresSteam) envs.AddRange(SteamAccounts.CredentialEnvs);
+        Credentials.Load(envs);
+
+        foreach (var env in envs.Where(env => string.IsNullOrEmpty(Environment.GetEnvironmentVariable(env))))
+            throw new MbssException(ErrorKind.Config, $"Environment variable {env} is not set!");
+
         #endregion
 
         #region Preflight Checks
 
-        if (!Repository.IsValid(Directory.GetCurrentDirectory()))
+        if (!Repository.IsValid(Workspace.RepositoryPath))
+            throw new MbssException(ErrorKind.Config, "MBSS is not running inside a Git repository, aborting.");
+
+        if (!Workspace.IsManaged())
+            throw new MbssException(ErrorKind.Config,
+                "Repository has no versions.json or .mbss marker, aborting. " +
+                "Create an empty .mbss file if this is really where versions go.");
+
+        // versions.json is often edited on GitHub, those are merged before anything is read, committed or pushed
+        if (!Options.Offline && !Workspace.IsBare) PullMain();
+
+        // The .gitignore keeps downloads/ out of the repository, so it's always brought up to date first
+        var supportFiles = await SupportFiles.Sync();
+        if (supportFiles.Count > 0) await CommitAndPush("chore: update support files", supportFiles.ToArray());
+
+        if (Workspace.IsBare && Lfs.Enabled)
+            throw new MbssException(ErrorKind.Config, "Git LFS is not supported when operating on a bare repository!");
+
+        GitHubTool.MigrateLegacyDirectory();
+        TempPath.Sweep();
+        Lfs.Register();
+
+        var tools = new List<GitHubTool> { GitHubTool.GenericStripper };
+        if (depotSource.RequiresDepotDownloader) tools.Add(GitHubTool.DepotDownloader);
+        foreach (var tool in tools.Where(tool => !tool.IsInstalled))
         {
-            AnsiConsole.MarkupLine("[red]MBSS is not running inside a Git repository, aborting.[/]");
-            return;
+            if (tool.PathOverride != null)
+                throw new MbssException(ErrorKind.Config,
+                    $"Configured {tool.Name} path {tool.PathOverride} does not exist!");
+
+            if (Options.Offline)
+                throw new MbssException(ErrorKind.Config,
+                    $"Offline mode requires {tool.ExecutablePath} to already exist!");
+
+            await tool.Install(client);
         }
 
-        if (!File.Exists(".gitignore"))
+        if (!Options.SkipPreflight) await Preflight.Run(client, depotSource);
+
+        Directory.CreateDirectory("downloads");
+        Directory.CreateDirectory("versions");
+
+        #endregion
+    }
+
+    private static async Task<bool> ProcessVersion(IDepotSource depotSource, BeatSaberVersion version,
+        string? details = null)
+    {
+        var versionPath = VersionPath(version);
+        var downloadPath = Path.GetFullPath(Path.Combine("downloads", version.Version));
+
+        // Isolated runs strip into downloads/ and only move the finished version into the checkout
+        var isolate = Environment.GetEnvironmentVariable("MBSS_ISOLATE")?.ToLowerInvariant() is "1" or "true";
+        var stripPath = isolate ? $"{downloadPath}.staging" : versionPath;
+        if (isolate && Directory.Exists(stripPath)) Directory.Delete(stripPath, true);
+
+        var provenance = await GetAndStrip(depotSource, version, downloadPath, stripPath);
+        if (isolate) FileCopy.MoveDirectory(stripPath, versionPath);
+        AnsiConsole.MarkupLine($"[green]Version {version.Version} stripped![/]");
+
+        var message = details == null ? $"chore: v{version.Version}" : $"chore: v{version.Version}\n\n{details}";
+
+        string? previousTip;
+        using (var repo = new Repository(Workspace.RepositoryPath)) previousTip = VersionTree.MainTip(repo)?.Sha;
+
+        try
         {
-            AnsiConsole.MarkupLine("[red]Git repository does not have a .gitignore, aborting.[/]");
-            AnsiConsole.MarkupLine("[red]It is absolutely necessary to ignore the bin/ and downloads/ directories![/]");
-            return;
+            if (Lfs.Patterns.Length > 0) await Lfs.WriteAttributes(versionPath, Lfs.Patterns);
+
+            await Hooks.Run("pre-commit", version, versionPath);
+
+            // Written last so they cover whatever the hook changed as well
+            if (provenance != null) await Provenance.Write(provenance, versionPath);
+            if (Checksums.Enabled) await Checksums.Write(versionPath);
+            if (!await CommitAndPush(message, versionPath)) return false;
+            if (OrphanBranches.Enabled) OrphanBranches.Publish(version, message, Author());
+        }
+        catch
+        {
+            Rollback(previousTip, versionPath);
+            throw;
         }
 
-        if (!File.Exists("bin/DepotDownloader.exe")) await GetDepotDownloader(client);
-        if (!File.Exists("bin/GenericStripper.exe")) await GetGenericStripper(client);
+        await Hooks.Run("post-push", version, versionPath);
+        if (Cas.Enabled && !Workspace.IsBare) await Cas.Deduplicate(versionPath);
+        if (Changelog.Enabled && await Changelog.Update(version))
+            await CommitAndPush($"docs: changelog for v{version.Version}", Path.GetFullPath(Changelog.FileName));
+        await Notifications.VersionPublished(version, versionPath);
+        await Publishers.Publish(version, versionPath);
 
-        var downloadDir = new DirectoryInfo("downloads");
-        var versionsDir = new DirectoryInfo("versions");
+        // The committed tree is all a bare repository needs, the scratch copy would only take up space
+        if (Workspace.IsBare) Directory.Delete(versionPath, true);
+        return true;
+    }
 
-        if (!downloadDir.Exists) downloadDir.Create();
-        if (!versionsDir.Exists) versionsDir.Create();
+    // Nothing can be undone once main is pushed, until then a failed version must not leave a commit or files behind
+    private static void Rollback(string? previousTip, string versionPath)
+    {
+        AnsiConsole.MarkupLine("[yellow]Rolling back the failed version...[/]");
 
-        #endregion
+        using (var repo = new Repository(Workspace.RepositoryPath))
+        {
+            var commit = previousTip == null ? null : repo.Lookup<Commit>(previousTip);
+            if (Workspace.IsBare && commit != null)
+                repo.Refs.Add("refs/heads/main", commit.Id, true);
+            else if (!Workspace.IsBare && commit != null && repo.Head.Tip?.Sha != previousTip)
+                repo.Reset(ResetMode.Mixed, commit);
+            else if (!Workspace.IsBare)
+                Commands.Unstage(repo, versionPath);
+        }
 
+        if (Workspace.IsManaged() && Directory.Exists(versionPath)) Directory.Delete(versionPath, true);
+    }
 
-        foreach (var version in versions)
+    private static string VersionPath(BeatSaberVersion version)
+    {
+        return Path.GetFullPath(Path.Combine("versions", version.Version));
+    }
+
+    private static async Task<List<BeatSaberVersion>> LoadVersions()
+    {
+        try
+        {
+            return await BeatSaberVersion.LoadAll() ??
+                   throw new MbssException(ErrorKind.Config, "Failed to parse versions.json!");
+        }
+        catch (JsonException e)
+        {
+            throw new MbssException(ErrorKind.Config, $"Failed to parse versions.json: {e.Message}", e);
+        }
+    }
+
+    private static async Task<bool> CommitAndPush(string message, params string[] paths)
+    {
+        using var repo = new Repository(Workspace.RepositoryPath);
+        var author = Author();
+
+        if (Workspace.IsBare)
         {
-            var downloadPath = Path.Combine(downloadDir.FullName, $"{version.Version}");
-            var versionPath = Path.Combine(versionsDir.FullName, $"{version.Version}");
-            if (Directory.Exists(versionPath))
+            using (RunReport.Stage("commit"))
             {
-                AnsiConsole.MarkupLine($"[yellow]Version {version.Version} already exists, skipping...[/]");
-                continue;
+                if (!Workspace.CommitToObjectDatabase(repo, message, author, paths)) return false;
             }
+        }
+        else
+        {
+            var status = repo.RetrieveStatus();
+            if (!status.IsDirty) return false; // No changes, skip
 
-            await GetAndStrip(version, downloadPath, versionPath);
-            AnsiConsole.MarkupLine($"[green]Version {version.Version} stripped![/]");
+            using (RunReport.Stage("commit"))
+            {
+                Commands.Stage(repo, paths);
+                repo.Commit(message, author, author);
+            }
+        }
 
-            using var repo = new Repository(Directory.GetCurrentDirectory());
-            var author = new Signature(Environment.GetEnvironmentVariable("GIT_AUTHOR_NAME"),
-                Environment.GetEnvironmentVariable("GIT_AUTHOR_EMAIL"), DateTimeOffset.Now);
+        if (RunReport.Current != null) RunReport.Current.Commit = VersionTree.MainTip(repo)?.Sha;
+        await PushMain(repo);
+        return true;
+    }
 
-            var status = repo.RetrieveStatus();
-            if (!status.IsDirty) continue; // No changes, skip
+    private static void PullMain()
+    {
+        using var repo = new Repository(Workspace.RepositoryPath);
+        var remote = repo.Network.Remotes["origin"];
+        if (remote == null) return;
 
-            Commands.Stage(repo, versionPath);
-            repo.Commit($"chore: v{version.Version}", author, author);
+        AnsiConsole.MarkupLine("[yellow]Fetching branches from origin...[/]");
+        Commands.Fetch(repo, remote.Name, new[] { "+refs/heads/*:refs/remotes/origin/*" },
+            Credentials.GitFetchOptions, null);
 
-            var remote = repo.Network.Remotes["origin"];
-            var options = new PushOptions
-            {
-                CredentialsProvider = (_, _, _) => new UsernamePasswordCredentials
-                {
-                    Username = Environment.GetEnvironmentVariable("GIT_AUTHOR_NAME"),
-                    Password = Environment.GetEnvironmentVariable("GITHUB_TOKEN")
-                }
-            };
+        var remoteMain = repo.Branches["origin/main"];
+        if (remoteMain == null) return;
 
-            if (remote != null) repo.Network.Push(remote, @"refs/heads/main", options);
-        }
+        var result = repo.Merge(remoteMain.Tip, Author(), new MergeOptions { FailOnConflict = true });
+        if (result.Status == MergeStatus.Conflicts)
+            throw new MbssException(ErrorKind.Repository,
+                "main conflicts with origin/main, merge them manually before running MBSS!");
+
+        if (result.Status != MergeStatus.UpToDate) AnsiConsole.MarkupLine("[green]Merged changes from origin/main[/]");
+    }
+
+    private static Signature Author()
+    {
+        return new Signature(Environment.GetEnvironmentVariable("GIT_AUTHOR_NAME"),
+            Environment.GetEnvironmentVariable("GIT_AUTHOR_EMAIL"), DateTimeOffset.Now);
     }
 
-    private static async Task GetAndStrip(BeatSaberVersion version, string downloadPath, string versionPath)
+    private static async Task PushMain(Repository repo)
     {
-        var depotDownloader = new Process
+        var remote = repo.Network.Remotes["origin"];
+        var options = new PushOptions { CredentialsProvider = Credentials.GitHandler };
+
+        if (remote == null || Options.Offline) return;
+
+        using (RunReport.Stage("push"))
         {
-            StartInfo =
+            // LFS objects have to be on the server before the pointers referencing them are pushed
+            if (Lfs.Enabled) await Lfs.Upload(remote.PushUrl);
+            try
+            {
+                repo.Network.Push(remote, @"refs/heads/main", options);
+                if (Lfs.Enabled) Lfs.Pushed();
+            }
+            catch (LibGit2SharpException e)
             {
-                FileName = "bin/DepotDownloader.exe",
-                Arguments =
-                    $"-app 620980 -depot 620981 -manifest \"{version.Manifest}\" -dir {downloadPath} -remember-password -username \"{Environment.GetEnvironmentVariable("STEAM_USERNAME")}\" -password \"{Environment.GetEnvironmentVariable("STEAM_PASSWORD")}\""
+                throw new MbssException(ErrorKind.GitPush, $"Failed to push main: {e.Message}", e);
             }
+        }
+
+        if (RunReport.Current != null) RunReport.Current.Pushed = true;
+    }
+
+    internal static IDepotSource CreateDepotSource()
+    {
+        // Offline runs can only read depots that were provisioned ahead of time
+        var source = Options.Offline
+            ? "local"
+            : Environment.GetEnvironmentVariable("MBSS_DEPOT_SOURCE") ?? "depotdownloader";
+        return source.ToLowerInvariant() switch
+        {
+            "depotdownloader" => new StoreSource(new DepotDownloaderSource(), new OculusSource(Http.CreateClient())),
+            "steamkit" => new StoreSource(new SteamKitSource(), new OculusSource(Http.CreateClient())),
+            "local" => new LocalDepotSource(Environment.GetEnvironmentVariable("MBSS_LOCAL_DEPOT_PATH") ?? "depots"),
+            _ => throw new MbssException(ErrorKind.Config, $"Unknown depot source {source}!")
         };
+    }
+
+    private static async Task<Provenance.Build?> GetAndStrip(IDepotSource depotSource, BeatSaberVersion version,
+        string downloadPath, string versionPath)
+    {
+        var startedOn = DateTimeOffset.UtcNow;
+        DiskSpace.CheckVersion(downloadPath, version);
+        await Hooks.Run("pre-download", version, versionPath);
+        string depotPath;
+        using (RunReport.Stage("download")) depotPath = await depotSource.Fetch(version, downloadPath);
+        if (RunReport.Current is { DownloadedBytes: 0 } && depotPath == downloadPath)
+            RunReport.Current.DownloadedBytes = Sizes.OfDirectory(depotPath);
+        await VersionCheck.Run(version, depotPath);
+
+        var stripperMode = version.StripperMode ??
+                           Environment.GetEnvironmentVariable("MBSS_STRIPPER_MODE") ?? "beatsaber";
+        var stripperArgs = version.StripperArgs ?? Environment.GetEnvironmentVariable("MBSS_STRIPPER_ARGS");
+
+        int exitCode;
+        using (RunReport.Stage("strip"))
+        {
+            exitCode = await RunGenericStripper(
+                $"strip -m {stripperMode} -p \"{depotPath}\" -o \"{versionPath}\" {stripperArgs}".TrimEnd(),
+                versionPath);
+        }
+
+        using var processStage = RunReport.Stage("process");
+        Provenance.Build? provenance = null;
+        try
+        {
+            if (exitCode != 0)
+                throw new MbssException(ErrorKind.StripFailure, $"GenericStripper failed with exit code {exitCode}!");
+
+            FileFilter.FromEnvironment().Apply(versionPath);
+            await VersionFiles.Write(version, versionPath, stripperMode, stripperArgs);
+            await PostProcessor.Run(version, versionPath);
+            await Hooks.Run("post-strip", version, versionPath);
+            await AssemblyVerifier.Verify(version, versionPath);
+            await SecretScanner.Scan(versionPath);
+            await LargeFiles.Check(versionPath);
+            if (Provenance.Enabled)
+                provenance = await Provenance.Record(version, depotPath, stripperMode, stripperArgs, startedOn);
+        }
+        catch
+        {
+            // Leaving the output behind would make the next run skip this version
+            if (Workspace.IsManaged() && Directory.Exists(versionPath)) Directory.Delete(versionPath, true);
+            throw;
+        }
 
-        depotDownloader.Start();
-        await depotDownloader.WaitForExitAsync();
+        if (Directory.Exists(downloadPath)) Directory.Delete(downloadPath, true);
+        return provenance;
+    }
 
-        var genericStripper = new Process
+    private static async Task<int> RunGenericStripper(string arguments, string versionPath)
+    {
+        for (var attempt = 0;; attempt++)
         {
-            StartInfo =
+            using var genericStripper = new Process
+            {
+                StartInfo =
+                {
+                    FileName = GitHubTool.GenericStripper.ExecutablePath,
+                    Arguments = arguments,
+                    RedirectStandardOutput = true,
+                    RedirectStandardError = true,
+                    UseShellExecute = false
+                }
+            };
+
+            genericStripper.Start();
+            using var log = ChildLog.Open("GenericStripper");
+            using var watchdog = Watchdog.ForStage(genericStripper, "GenericStripper", "STRIP");
+            genericStripper.OutputDataReceived += (_, e) => ForwardOutput(watchdog, log, "stdout", e.Data);
+            genericStripper.ErrorDataReceived += (_, e) => ForwardOutput(watchdog, log, "stderr", e.Data);
+            genericStripper.BeginOutputReadLine();
+            genericStripper.BeginErrorReadLine();
+            await genericStripper.WaitForExitAsync();
+            log.Write("mbss", $"Exited with code {genericStripper.ExitCode}");
+
+            try
             {
-                FileName = "bin/GenericStripper.exe",
-                Arguments = $"strip -m beatsaber -p \"{downloadPath}\" -o \"{versionPath}\""
+                watchdog.ThrowIfTripped();
+                return genericStripper.ExitCode;
             }
-        };
+            catch (TimeoutException e)
+            {
+                // A killed stripper leaves a partial version behind
+                if (Workspace.IsManaged() && Directory.Exists(versionPath)) Directory.Delete(versionPath, true);
+                if (attempt >= Watchdog.Retries) throw;
 
-        genericStripper.Start();
-        await genericStripper.WaitForExitAsync();
+                AnsiConsole.MarkupLine($"[yellow]{e.Message.EscapeMarkup()} Retrying...[/]");
+            }
+        }
+    }
 
-        if (Directory.Exists(downloadPath)) Directory.Delete(downloadPath, true);
+    private static void ForwardOutput(Watchdog watchdog, ChildLog log, string stream, string? line)
+    {
+        if (line == null) return;
+
+        watchdog.Output(line);
+        log.Write(stream, line);
     }
 
     private static void InitConsole()
@@ -181,52 +655,4 @@ internal abstract class Program
             Environment.SetEnvironmentVariable(split[0], split[1]);
         }
     }
-
-    private static async Task GetDepotDownloader(HttpClient client)
-    {
-        AnsiConsole.MarkupLine("[yellow]DepotDownloader.exe does not exist, downloading...[/]");
-
-        var res = await client.GetAsync("https://api.github.com/repos/SteamRE/DepotDownloader/releases/latest");
-        if (res.StatusCode != HttpStatusCode.OK) throw new Exception("Failed to get DepotDownloader release!");
-
-        var latestRelease =
-            JsonConvert.DeserializeObject<Dictionary<string, dynamic>>(res.Content.ReadAsStringAsync().Result);
-        if (latestRelease == null) throw new Exception("Failed to parse DepotDownloader release!");
-
-        var assets = latestRelease["assets"] as JArray;
-        var asset = assets?.FirstOrDefault(x => x["name"]?.ToString().Contains("windows-x64") ?? false);
-        if (asset == null) throw new Exception("Failed to find a DepotDownloader asset for this system!");
-
-        var assetRes = client.GetAsync(asset["browser_download_url"]?.ToString()).Result;
-        if (assetRes.StatusCode != HttpStatusCode.OK)
-            throw new Exception("Failed to download DepotDownloader asset!");
-
-        await using var assetStream = assetRes.Content.ReadAsStreamAsync().Result;
-        using var archive = new ZipArchive(assetStream);
-        archive.ExtractToDirectory(Path.Combine(Directory.GetCurrentDirectory(), "bin"));
-    }
-
-    private static async Task GetGenericStripper(HttpClient client)
-    {
-        AnsiConsole.MarkupLine("[yellow]GenericStripper.exe does not exist, downloading...[/]");
-
-        var res = await client.GetAsync("https://api.github.com/repos/beat-forge/GenericStripper/releases/latest");
-        if (res.StatusCode != HttpStatusCode.OK) throw new Exception("Failed to get GenericStripper release!");
-
-        var latestRelease =
-            JsonConvert.DeserializeObject<Dictionary<string, dynamic>>(res.Content.ReadAsStringAsync().Result);
-        if (latestRelease == null) throw new Exception("Failed to parse GenericStripper release!");
-
-        var assets = latestRelease["assets"] as JArray;
-        var asset = assets?.FirstOrDefault(x => x["name"]?.ToString().Contains("GenericStripper") ?? false);
-        if (asset == null) throw new Exception("Failed to find a GenericStripper asset for this system!");
-
-        var assetRes = client.GetAsync(asset["browser_download_url"]?.ToString()).Result;
-        if (assetRes.StatusCode != HttpStatusCode.OK)
-            throw new Exception("Failed to download GenericStripper asset!");
-
-        await using var assetStream = assetRes.Content.ReadAsStreamAsync().Result;
-        using var archive = new ZipArchive(assetStream);
-        archive.ExtractToDirectory(Path.Combine(Directory.GetCurrentDirectory(), "bin"));
-    }
 }
\ No newline at end of file
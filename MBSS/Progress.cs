@@ -0,0 +1,35 @@
+﻿using Newtonsoft.Json;
+using Newtonsoft.Json.Linq;
+
+namespace MBSS;
+
+internal static class Progress
+{
+    private static TextWriter? _writer;
+
+    public static bool Enabled => _writer != null;
+
+    public static void Init()
+    {
+        if (Options.Value("--progress") != "json") return;
+
+        // Events own stdout, everything that would normally be printed there moves to stderr
+        _writer = Console.Out;
+        Console.SetOut(Console.Error);
+    }
+
+    public static void Emit(string type, object data)
+    {
+        if (_writer == null) return;
+
+        var obj = JObject.FromObject(data);
+        obj.AddFirst(new JProperty("time", DateTimeOffset.UtcNow));
+        obj.AddFirst(new JProperty("event", type));
+
+        lock (_writer)
+        {
+            _writer.WriteLine(obj.ToString(Formatting.None));
+            _writer.Flush();
+        }
+    }
+}
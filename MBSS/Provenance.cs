@@ -0,0 +1,152 @@
+﻿using System.Reflection;
+using System.Security.Cryptography;
+using System.Text;
+using MBSS.Tools;
+using Newtonsoft.Json;
+using Newtonsoft.Json.Linq;
+using Spectre.Console;
+
+namespace MBSS;
+
+// An in-toto statement with a SLSA provenance predicate, wrapped in a DSSE envelope
+internal static class Provenance
+{
+    public const string FileName = "provenance.intoto.json";
+    private const string PayloadType = "application/vnd.in-toto+json";
+
+    private static string? KeyPath => Environment.GetEnvironmentVariable("MBSS_PROVENANCE_KEY");
+
+    public static bool Enabled =>
+        !string.IsNullOrEmpty(KeyPath) ||
+        Environment.GetEnvironmentVariable("MBSS_PROVENANCE")?.ToLowerInvariant() is "1" or "true";
+
+    // The depot is gone by the time the version is committed, so its side is recorded right after stripping
+    public static async Task<Build> Record(BeatSaberVersion version, string depotPath, string stripperMode,
+        string? stripperArgs, DateTimeOffset startedOn)
+    {
+        // Only the assemblies feed the stripper, hashing the whole depot would take longer than the download
+        var inputs = await Checksums.Compute(depotPath,
+            x => x.EndsWith(".dll", StringComparison.OrdinalIgnoreCase) && !x.Contains(".DepotDownloader"));
+
+        var dependencies = new JArray(
+            new JObject
+            {
+                ["uri"] = version.IsOculus
+                    ? $"oculus://binary/{version.OculusBinaryId}"
+                    : $"steam://depot/{version.DepotId}/manifest/{version.Manifest}",
+                ["annotations"] = new JObject { ["beta"] = version.Beta }
+            });
+        foreach (var tool in new[] { GitHubTool.DepotDownloader, GitHubTool.GenericStripper })
+            dependencies.Add(new JObject
+            {
+                ["name"] = tool.Name,
+                ["uri"] = $"https://github.com/{tool.Repository}",
+                ["annotations"] = new JObject { ["version"] = tool.InstalledVersion ?? tool.PathOverride }
+            });
+        foreach (var (name, hash) in inputs)
+            dependencies.Add(new JObject { ["name"] = name, ["digest"] = new JObject { ["sha256"] = hash } });
+
+        return new Build(version, stripperMode, stripperArgs, startedOn, dependencies);
+    }
+
+    // Written once LFS attributes and the pre-commit hook are done, the subjects have to match the committed tree
+    public static async Task Write(Build build, string versionPath)
+    {
+        var (version, stripperMode, stripperArgs, startedOn, dependencies) = build;
+        var output = Path.Combine(versionPath, FileName);
+        var subjects = await Checksums.Compute(versionPath, x => x != output);
+
+        var statement = new JObject
+        {
+            ["_type"] = "https://in-toto.io/Statement/v1",
+            ["subject"] = new JArray(subjects.Select(x =>
+                new JObject { ["name"] = x.Name, ["digest"] = new JObject { ["sha256"] = x.Hash } })),
+            ["predicateType"] = "https://slsa.dev/provenance/v1",
+            ["predicate"] = new JObject
+            {
+                ["buildDefinition"] = new JObject
+                {
+                    ["buildType"] = "https://github.com/beat-forge/MBSS/strip@v1",
+                    ["externalParameters"] = new JObject
+                    {
+                        ["version"] = version.Version,
+                        ["manifest"] = version.Manifest,
+                        ["stripper_mode"] = stripperMode,
+                        ["stripper_args"] = stripperArgs
+                    },
+                    ["resolvedDependencies"] = dependencies
+                },
+                ["runDetails"] = new JObject
+                {
+                    ["builder"] = new JObject
+                    {
+                        ["id"] = "https://github.com/beat-forge/MBSS",
+                        ["version"] = new JObject { ["MBSS"] = MbssVersion }
+                    },
+                    ["metadata"] = new JObject
+                    {
+                        ["startedOn"] = startedOn,
+                        ["finishedOn"] = DateTimeOffset.UtcNow
+                    }
+                }
+            }
+        };
+
+        var payload = Encoding.UTF8.GetBytes(statement.ToString(Formatting.None));
+        var signatures = new JArray();
+        if (!string.IsNullOrEmpty(KeyPath))
+        {
+            var (keyId, signature) = Sign(await File.ReadAllTextAsync(KeyPath), payload);
+            signatures.Add(new JObject { ["keyid"] = keyId, ["sig"] = Convert.ToBase64String(signature) });
+        }
+        else
+        {
+            AnsiConsole.MarkupLine("[yellow]MBSS_PROVENANCE_KEY is not set, the provenance will be unsigned.[/]");
+        }
+
+        var envelope = new JObject
+        {
+            ["payloadType"] = PayloadType,
+            ["payload"] = Convert.ToBase64String(payload),
+            ["signatures"] = signatures
+        };
+        await File.WriteAllTextAsync(output, envelope.ToString(Formatting.Indented));
+    }
+
+    public record Build(BeatSaberVersion Version, string StripperMode, string? StripperArgs,
+        DateTimeOffset StartedOn, JArray Dependencies);
+
+    private static string MbssVersion =>
+        typeof(Provenance).Assembly.GetCustomAttribute<AssemblyInformationalVersionAttribute>()?.InformationalVersion ??
+        "unknown";
+
+    private static (string KeyId, byte[] Signature) Sign(string pem, byte[] payload)
+    {
+        // DSSE signs the pre-authentication encoding, not the payload itself
+        var pae = Encoding.UTF8.GetBytes($"DSSEv1 {PayloadType.Length} {PayloadType} {payload.Length} ")
+            .Concat(payload)
+            .ToArray();
+
+        // PKCS#8 keys don't say which algorithm they are for, so RSA is tried first
+        try
+        {
+            using var rsa = RSA.Create();
+            rsa.ImportFromPem(pem);
+            return (KeyId(rsa.ExportSubjectPublicKeyInfo()),
+                rsa.SignData(pae, HashAlgorithmName.SHA256, RSASignaturePadding.Pkcs1));
+        }
+        catch (Exception e) when (e is ArgumentException or CryptographicException)
+        {
+            using var ecdsa = ECDsa.Create();
+            ecdsa.ImportFromPem(pem);
+            return (KeyId(ecdsa.ExportSubjectPublicKeyInfo()),
+                ecdsa.SignData(pae, HashAlgorithmName.SHA256, DSASignatureFormat.Rfc3279DerSequence));
+        }
+    }
+
+    private static string KeyId(byte[] publicKey)
+    {
+        return Environment.GetEnvironmentVariable("MBSS_PROVENANCE_KEY_ID") ??
+               Convert.ToHexString(SHA256.HashData(publicKey)).ToLowerInvariant();
+    }
+}
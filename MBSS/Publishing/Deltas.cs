@@ -0,0 +1,122 @@
+﻿using System.IO.Compression;
+using System.Security.Cryptography;
+using LibGit2Sharp;
+using Newtonsoft.Json;
+using ZstdSharp;
+using ZstdSharp.Unsafe;
+
+namespace MBSS.Publishing;
+
+// Changed files are zstd patches that use the previous file as dictionary, the same as zstd --patch-from. Added files,
+// and ones too large to patch in memory, are stored whole
+internal static class Deltas
+{
+    private const long MaxPatchSize = 512L * 1024 * 1024;
+    private const int PatchLevel = 19;
+
+    public static bool Enabled =>
+        Environment.GetEnvironmentVariable("MBSS_DELTAS")?.ToLowerInvariant() is "1" or "true";
+
+    public static async Task<string?> Create(BeatSaberVersion version, string outputDirectory)
+    {
+        var versions = File.Exists("versions.json") ? await BeatSaberVersion.LoadAll() : null;
+        if (versions == null) return null;
+
+        using var repo = new Repository(Workspace.RepositoryPath);
+        var tree = VersionTree.Find(repo, version.Version);
+        if (tree == null || VersionTree.FindPrevious(repo, versions, version.Version) is not { } previous)
+            return null;
+
+        var changes = repo.Diff.Compare<TreeChanges>(previous.Tree, tree);
+        var removed = changes.Deleted.Select(x => x.OldPath)
+            .Concat(changes.Renamed.Select(x => x.OldPath))
+            .ToList();
+        var whole = changes.Added.Concat(changes.TypeChanged).Select(x => x.Path).ToList();
+
+        var deltaPath = Path.Combine(outputDirectory, $"BeatSaber-{previous.Version}-to-{version.Version}.delta.zip");
+        if (File.Exists(deltaPath)) File.Delete(deltaPath);
+
+        await using var file = File.Create(deltaPath);
+        using var archive = new ZipArchive(file, ZipArchiveMode.Create);
+        var patches = new Dictionary<string, object>();
+        foreach (var change in changes.Modified.Concat(changes.Renamed))
+        {
+            var oldBlob = (Blob)previous.Tree[change.OldPath].Target;
+            var newBlob = (Blob)tree[change.Path].Target;
+            if (oldBlob.Size > MaxPatchSize || newBlob.Size > MaxPatchSize)
+            {
+                whole.Add(change.Path);
+                continue;
+            }
+
+            var oldBytes = await ReadAll(oldBlob);
+            var newBytes = await ReadAll(newBlob);
+            var windowLog = WindowLog(Math.Max(oldBytes.Length, newBytes.Length));
+
+            // Already compressed, zipping it again would only cost time
+            var entry = archive.CreateEntry($"patches/{change.Path}.zst", CompressionLevel.NoCompression);
+            await using (var target = entry.Open()) await target.WriteAsync(Patch(oldBytes, newBytes, windowLog));
+
+            patches[change.Path] = new
+            {
+                from = change.OldPath,
+                from_sha256 = Hash(oldBytes),
+                sha256 = Hash(newBytes),
+                window_log = windowLog
+            };
+        }
+
+        var files = new Dictionary<string, string>();
+        foreach (var path in whole)
+        {
+            var blob = (Blob)tree[path].Target;
+            await using (var source = blob.GetContentStream())
+                files[path] = Convert.ToHexString(await SHA256.HashDataAsync(source)).ToLowerInvariant();
+
+            await using var content = blob.GetContentStream();
+            await using var target = archive.CreateEntry($"files/{path}", CompressionLevel.Optimal).Open();
+            await content.CopyToAsync(target);
+        }
+
+        await using (var writer = new StreamWriter(archive.CreateEntry("delta.json").Open()))
+            await writer.WriteAsync(JsonConvert.SerializeObject(new
+            {
+                from = previous.Version,
+                to = version.Version,
+                removed,
+                files,
+                patches
+            }, Formatting.Indented));
+
+        return deltaPath;
+    }
+
+    // Applying one needs the same dictionary and a window of at least window_log, which delta.json records
+    private static byte[] Patch(byte[] oldBytes, byte[] newBytes, int windowLog)
+    {
+        using var compressor = new Compressor(PatchLevel);
+        compressor.SetParameter(ZSTD_cParameter.ZSTD_c_windowLog, windowLog);
+        compressor.SetParameter(ZSTD_cParameter.ZSTD_c_enableLongDistanceMatching, 1);
+        compressor.LoadDictionary(oldBytes);
+        return compressor.Wrap(newBytes).ToArray();
+    }
+
+    // Large enough to keep all of the old file in reach, like zstd --patch-from picks it
+    private static int WindowLog(long size)
+    {
+        return Math.Clamp((int)Math.Ceiling(Math.Log2(size + 1)), 10, 30);
+    }
+
+    private static async Task<byte[]> ReadAll(Blob blob)
+    {
+        await using var content = blob.GetContentStream();
+        using var buffer = new MemoryStream((int)blob.Size);
+        await content.CopyToAsync(buffer);
+        return buffer.ToArray();
+    }
+
+    private static string Hash(byte[] bytes)
+    {
+        return Convert.ToHexString(SHA256.HashData(bytes)).ToLowerInvariant();
+    }
+}
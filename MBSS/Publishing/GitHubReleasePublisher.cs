@@ -0,0 +1,84 @@
+﻿using System.Net;
+using System.Net.Http.Headers;
+using System.Text;
+using Newtonsoft.Json;
+using Newtonsoft.Json.Linq;
+
+namespace MBSS.Publishing;
+
+internal class GitHubReleasePublisher : IPublisher
+{
+    private readonly string _tagTemplate;
+
+    public GitHubReleasePublisher(string? tagTemplate)
+    {
+        _tagTemplate = tagTemplate ?? "v{version}";
+    }
+
+    public string Name => "GitHub Releases";
+
+    public string? DownloadUrl(string remoteUrl, string version)
+    {
+        var match = Preflight.GitHubRemoteRegex.Match(remoteUrl);
+        if (!match.Success) return null;
+
+        return $"https://github.com/{match.Groups["owner"].Value}/{match.Groups["repo"].Value}/releases/download/" +
+               $"{Uri.EscapeDataString(Tag(version))}/{Uri.EscapeDataString(Publishers.ArchiveName(version))}";
+    }
+
+    public async Task Publish(HttpClient client, VersionArtifact artifact)
+    {
+        var remoteUrl = VersionFiles.PublicRemote() ??
+                        throw new MbssException(ErrorKind.Config, "No origin remote configured!");
+
+        var match = Preflight.GitHubRemoteRegex.Match(remoteUrl);
+        if (!match.Success) throw new MbssException(ErrorKind.Config, $"{remoteUrl} is not a GitHub repository!");
+
+        var api = $"https://api.github.com/repos/{match.Groups["owner"].Value}/{match.Groups["repo"].Value}";
+        var tag = Tag(artifact.Version.Version);
+
+        // Re-publishing a version reuses its release and only replaces the archive
+        var res = await GitHubApi.Get(client, $"{api}/releases/tags/{Uri.EscapeDataString(tag)}");
+        if (res.StatusCode == HttpStatusCode.NotFound)
+        {
+            var body = JsonConvert.SerializeObject(new
+            {
+                tag_name = tag,
+                target_commitish = artifact.Commit,
+                name = $"Beat Saber {artifact.Version.Version}",
+                body = $"Stripped assemblies of Beat Saber {artifact.Version.Version} " +
+                       $"(manifest {artifact.Version.Manifest})."
+            });
+            res = await GitHubApi.Send(client, HttpMethod.Post, $"{api}/releases",
+                () => new StringContent(body, Encoding.UTF8, "application/json"));
+        }
+
+        if (!res.IsSuccessStatusCode)
+            throw new MbssException(ErrorKind.Publish, $"Failed to create release {tag} ({(int)res.StatusCode})!");
+
+        var release = JObject.Parse(await res.Content.ReadAsStringAsync());
+        var uploadUrl = release["upload_url"]!.Value<string>()!.Split('{')[0];
+        foreach (var path in artifact.Files)
+        {
+            var name = Path.GetFileName(path);
+            var existing = release["assets"]?.FirstOrDefault(x => x["name"]?.Value<string>() == name);
+            if (existing != null)
+                await GitHubApi.Send(client, HttpMethod.Delete, $"{api}/releases/assets/{existing["id"]}");
+
+            res = await GitHubApi.Send(client, HttpMethod.Post, $"{uploadUrl}?name={Uri.EscapeDataString(name)}", () =>
+            {
+                var content = new StreamContent(File.OpenRead(path));
+                content.Headers.ContentType = new MediaTypeHeaderValue("application/octet-stream");
+                return content;
+            });
+            if (!res.IsSuccessStatusCode)
+                throw new MbssException(ErrorKind.Publish,
+                    $"Failed to upload {name} to release {tag} ({(int)res.StatusCode})!");
+        }
+    }
+
+    private string Tag(string version)
+    {
+        return _tagTemplate.Replace("{version}", version);
+    }
+}
@@ -0,0 +1,16 @@
+﻿namespace MBSS.Publishing;
+
+internal interface IPublisher
+{
+    string Name { get; }
+
+    Task Publish(HttpClient client, VersionArtifact artifact);
+}
+
+internal record VersionArtifact(BeatSaberVersion Version, string VersionPath, string ArchivePath, string? Commit,
+    string? DeltaPath = null)
+{
+    public string ArchiveName => Path.GetFileName(ArchivePath);
+
+    public IEnumerable<string> Files => DeltaPath == null ? new[] { ArchivePath } : new[] { ArchivePath, DeltaPath };
+}
@@ -0,0 +1,114 @@
+﻿using System.IO.Compression;
+using System.Net;
+using System.Security;
+using System.Text.RegularExpressions;
+using Microsoft.Extensions.FileSystemGlobbing;
+using Newtonsoft.Json.Linq;
+
+namespace MBSS.Publishing;
+
+internal class NuGetPublisher : IPublisher
+{
+    private const string ContentTypes =
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>" +
+        "<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">" +
+        "<Default Extension=\"nuspec\" ContentType=\"application/octet\" />" +
+        "<Default Extension=\"dll\" ContentType=\"application/octet\" />" +
+        "</Types>";
+
+    private static readonly Regex InvalidVersionChars = new("[^0-9A-Za-z.-]");
+
+    private readonly string _source;
+    private readonly string _apiKey;
+    private readonly string _id;
+    private readonly string _framework;
+    private readonly string[] _includes;
+
+    public NuGetPublisher(string source, string apiKey, string? id, string? framework, string? includes)
+    {
+        _source = source;
+        _apiKey = apiKey;
+        _id = id ?? "BeatSaber.GameLibs";
+        _framework = framework ?? "net472";
+        _includes = (includes ?? "**/Managed/*.dll").Split(';', StringSplitOptions.RemoveEmptyEntries);
+    }
+
+    public string Name => $"NuGet ({_id})";
+
+    public async Task Publish(HttpClient client, VersionArtifact artifact)
+    {
+        var packagePath = Path.ChangeExtension(artifact.ArchivePath, ".nupkg");
+        try
+        {
+            await Pack(artifact, packagePath);
+
+            var res = await client.SendAsync(await CreatePushRequest(client, packagePath));
+
+            // Feeds never allow a version to be replaced, a conflict means an earlier run already pushed it
+            if (res.StatusCode == HttpStatusCode.Conflict) return;
+            if (!res.IsSuccessStatusCode)
+                throw new MbssException(ErrorKind.Publish,
+                    $"Failed to push {_id} {artifact.Version.Version} ({(int)res.StatusCode})!");
+        }
+        finally
+        {
+            File.Delete(packagePath);
+        }
+    }
+
+    private async Task Pack(VersionArtifact artifact, string packagePath)
+    {
+        var matcher = new Matcher(StringComparison.OrdinalIgnoreCase);
+        matcher.AddIncludePatterns(_includes);
+        var assemblies = matcher.GetResultsInFullPath(artifact.VersionPath).ToList();
+        if (assemblies.Count == 0)
+            throw new MbssException(ErrorKind.Config,
+                $"No assemblies matched {string.Join(';', _includes)} in {artifact.VersionPath}!");
+
+        var remote = VersionFiles.PublicRemote() ?? string.Empty;
+        var nuspec = Assets.Render("package.nuspec", new Dictionary<string, string>
+        {
+            ["id"] = SecurityElement.Escape(_id),
+            ["version"] = InvalidVersionChars.Replace(artifact.Version.Version, "-"),
+            ["game_version"] = SecurityElement.Escape(artifact.Version.Version),
+            ["manifest"] = SecurityElement.Escape(artifact.Version.Manifest),
+            ["remote"] = SecurityElement.Escape(remote),
+            ["commit"] = artifact.Commit ?? string.Empty
+        });
+
+        await using var file = File.Create(packagePath);
+        using var package = new ZipArchive(file, ZipArchiveMode.Create);
+        await WriteEntry(package, $"{_id}.nuspec", nuspec);
+        await WriteEntry(package, "[Content_Types].xml", ContentTypes);
+        foreach (var assembly in assemblies)
+            package.CreateEntryFromFile(assembly, $"lib/{_framework}/{Path.GetFileName(assembly)}");
+    }
+
+    private async Task<HttpRequestMessage> CreatePushRequest(HttpClient client, string packagePath)
+    {
+        // V3 feeds advertise their push endpoint in the service index, anything else is taken as the endpoint itself
+        var pushUrl = _source;
+        if (_source.EndsWith("index.json", StringComparison.OrdinalIgnoreCase))
+        {
+            var index = JObject.Parse(await client.GetStringAsync(_source));
+            pushUrl = index["resources"]?
+                          .FirstOrDefault(x => x["@type"]?.Value<string>()?.StartsWith("PackagePublish/") == true)?
+                          ["@id"]?.Value<string>() ??
+                      throw new MbssException(ErrorKind.Config, $"{_source} does not support pushing packages!");
+        }
+
+        var content = new MultipartFormDataContent
+        {
+            { new StreamContent(File.OpenRead(packagePath)), "package", "package.nupkg" }
+        };
+        var req = new HttpRequestMessage(HttpMethod.Put, pushUrl) { Content = content };
+        req.Headers.Add("X-NuGet-ApiKey", _apiKey);
+        return req;
+    }
+
+    private static async Task WriteEntry(ZipArchive package, string name, string contents)
+    {
+        await using var writer = new StreamWriter(package.CreateEntry(name).Open());
+        await writer.WriteAsync(contents);
+    }
+}
@@ -0,0 +1,128 @@
+﻿using System.Formats.Tar;
+using System.IO.Compression;
+using LibGit2Sharp;
+using Spectre.Console;
+
+namespace MBSS.Publishing;
+
+internal static class Publishers
+{
+    public static List<IPublisher> FromEnvironment()
+    {
+        var publishers = new List<IPublisher>();
+
+        if (Environment.GetEnvironmentVariable("MBSS_GITHUB_RELEASES")?.ToLowerInvariant() is "1" or "true")
+            publishers.Add(new GitHubReleasePublisher(Environment.GetEnvironmentVariable("MBSS_RELEASE_TAG")));
+
+        var bucket = Environment.GetEnvironmentVariable("MBSS_S3_BUCKET");
+        if (!string.IsNullOrEmpty(bucket))
+        {
+            var accessKey = Environment.GetEnvironmentVariable("MBSS_S3_ACCESS_KEY_ID") ??
+                            Environment.GetEnvironmentVariable("AWS_ACCESS_KEY_ID");
+            var secretKey = Environment.GetEnvironmentVariable("MBSS_S3_SECRET_ACCESS_KEY") ??
+                            Environment.GetEnvironmentVariable("AWS_SECRET_ACCESS_KEY");
+            if (string.IsNullOrEmpty(accessKey) || string.IsNullOrEmpty(secretKey))
+                throw new MbssException(ErrorKind.Config,
+                    "MBSS_S3_BUCKET requires MBSS_S3_ACCESS_KEY_ID and MBSS_S3_SECRET_ACCESS_KEY!");
+
+            // MinIO and R2 need an endpoint, AWS can derive it from the region
+            var region = Environment.GetEnvironmentVariable("MBSS_S3_REGION") ?? "us-east-1";
+            var endpoint = Environment.GetEnvironmentVariable("MBSS_S3_ENDPOINT") ??
+                           $"https://s3.{region}.amazonaws.com";
+            publishers.Add(new S3Publisher(endpoint, bucket, Environment.GetEnvironmentVariable("MBSS_S3_PREFIX"),
+                region, accessKey, secretKey));
+        }
+
+        var nugetSource = Environment.GetEnvironmentVariable("MBSS_NUGET_SOURCE");
+        if (!string.IsNullOrEmpty(nugetSource))
+        {
+            var apiKey = Environment.GetEnvironmentVariable("MBSS_NUGET_API_KEY");
+            if (string.IsNullOrEmpty(apiKey))
+                throw new MbssException(ErrorKind.Config, "MBSS_NUGET_SOURCE requires MBSS_NUGET_API_KEY!");
+
+            publishers.Add(new NuGetPublisher(nugetSource, apiKey, Environment.GetEnvironmentVariable("MBSS_NUGET_ID"),
+                Environment.GetEnvironmentVariable("MBSS_NUGET_FRAMEWORK"),
+                Environment.GetEnvironmentVariable("MBSS_NUGET_INCLUDE")));
+        }
+
+        return publishers;
+    }
+
+    public static async Task Publish(BeatSaberVersion version, string versionPath)
+    {
+        var publishers = FromEnvironment();
+        if (publishers.Count == 0 || Options.Offline) return;
+
+        string? commit;
+        using (var repo = new Repository(Workspace.RepositoryPath))
+            commit = OrphanBranches.Enabled
+                ? repo.Branches[OrphanBranches.BranchName(version.Version)]?.Tip.Sha
+                : VersionTree.MainTip(repo)?.Sha;
+
+        using var stage = RunReport.Stage("publish");
+        var archivePath = await CreateArchive(version, versionPath);
+        var deltaPath = Deltas.Enabled ? await Deltas.Create(version, Path.GetDirectoryName(archivePath)!) : null;
+        try
+        {
+            using var client = Http.CreateClient();
+            var artifact = new VersionArtifact(version, versionPath, archivePath, commit, deltaPath);
+            var failed = new List<string>();
+            foreach (var publisher in publishers)
+            {
+                // The version is already pushed at this point, so the other channels still get their chance
+                try
+                {
+                    await publisher.Publish(client, artifact);
+                    AnsiConsole.MarkupLine($"[green]Published {version.Version} to {publisher.Name}[/]");
+                }
+                catch (Exception e)
+                {
+                    AnsiConsole.MarkupLine(
+                        $"[red]Failed to publish {version.Version} to {publisher.Name}: {e.Message.EscapeMarkup()}[/]");
+                    failed.Add(publisher.Name);
+                }
+            }
+
+            if (failed.Count > 0)
+                throw new MbssException(ErrorKind.Publish,
+                    $"Publishing to {string.Join(", ", failed)} failed, retry with `MBSS publish {version.Version}`!");
+        }
+        finally
+        {
+            File.Delete(archivePath);
+            if (deltaPath != null) File.Delete(deltaPath);
+        }
+    }
+
+    public static string ArchiveName(string version)
+    {
+        return $"BeatSaber-{version}.{ArchiveFormat}";
+    }
+
+    private static string ArchiveFormat =>
+        Environment.GetEnvironmentVariable("MBSS_ARCHIVE_FORMAT")?.ToLowerInvariant() ?? "zip";
+
+    private static async Task<string> CreateArchive(BeatSaberVersion version, string versionPath)
+    {
+        var format = ArchiveFormat;
+        var archivePath = Path.GetFullPath(Path.Combine("downloads", ArchiveName(version.Version)));
+        Directory.CreateDirectory(Path.GetDirectoryName(archivePath)!);
+        if (File.Exists(archivePath)) File.Delete(archivePath);
+
+        switch (format)
+        {
+            case "zip":
+                ZipFile.CreateFromDirectory(versionPath, archivePath, CompressionLevel.Optimal, false);
+                break;
+            case "tar.gz":
+                await using (var file = File.Create(archivePath))
+                await using (var gzip = new GZipStream(file, CompressionLevel.Optimal))
+                    await TarFile.CreateFromDirectoryAsync(versionPath, gzip, false);
+                break;
+            default:
+                throw new MbssException(ErrorKind.Config, $"Unsupported archive format {format}!");
+        }
+
+        return archivePath;
+    }
+}
@@ -0,0 +1,114 @@
+﻿using System.Net;
+using System.Security.Cryptography;
+using System.Text;
+using Newtonsoft.Json;
+using Newtonsoft.Json.Linq;
+
+namespace MBSS.Publishing;
+
+// Speaks plain SigV4 with path-style URLs, which AWS, MinIO and R2 all accept
+internal class S3Publisher : IPublisher
+{
+    private readonly string _endpoint;
+    private readonly string _bucket;
+    private readonly string _prefix;
+    private readonly string _region;
+    private readonly string _accessKey;
+    private readonly string _secretKey;
+
+    public S3Publisher(string endpoint, string bucket, string? prefix, string region, string accessKey,
+        string secretKey)
+    {
+        _endpoint = endpoint.TrimEnd('/');
+        _bucket = bucket;
+        _prefix = string.IsNullOrEmpty(prefix) ? string.Empty : $"{prefix.Trim('/')}/";
+        _region = region;
+        _accessKey = accessKey;
+        _secretKey = secretKey;
+    }
+
+    public string Name => $"s3://{_bucket}/{_prefix}";
+
+    public async Task Publish(HttpClient client, VersionArtifact artifact)
+    {
+        var key = $"{_prefix}{artifact.Version.Version}/{artifact.ArchiveName}";
+        string sha256;
+        await using (var file = File.OpenRead(artifact.ArchivePath))
+            sha256 = Convert.ToHexString(await SHA256.HashDataAsync(file)).ToLowerInvariant();
+
+        var res = await Send(client, HttpMethod.Put, key, sha256,
+            () => new StreamContent(File.OpenRead(artifact.ArchivePath)));
+        if (!res.IsSuccessStatusCode)
+            throw new MbssException(ErrorKind.Publish, $"Failed to upload {key} ({(int)res.StatusCode})!");
+
+        string? deltaKey = null;
+        if (artifact.DeltaPath != null)
+        {
+            deltaKey = $"{_prefix}{artifact.Version.Version}/{Path.GetFileName(artifact.DeltaPath)}";
+            var deltaHash = Hash(await File.ReadAllBytesAsync(artifact.DeltaPath));
+            res = await Send(client, HttpMethod.Put, deltaKey, deltaHash,
+                () => new StreamContent(File.OpenRead(artifact.DeltaPath)));
+            if (!res.IsSuccessStatusCode)
+                throw new MbssException(ErrorKind.Publish, $"Failed to upload {deltaKey} ({(int)res.StatusCode})!");
+        }
+
+        // The catalog is read-modify-write, runs publishing to the same prefix must not overlap
+        var catalogKey = $"{_prefix}catalog.json";
+        res = await Send(client, HttpMethod.Get, catalogKey, Hash(Array.Empty<byte>()));
+        var catalog = res.StatusCode == HttpStatusCode.NotFound
+            ? new JObject()
+            : res.IsSuccessStatusCode
+                ? JObject.Parse(await res.Content.ReadAsStringAsync())
+                : throw new MbssException(ErrorKind.Publish, $"Failed to read {catalogKey} ({(int)res.StatusCode})!");
+
+        catalog[artifact.Version.Version] = JObject.FromObject(new
+        {
+            manifest = artifact.Version.Manifest,
+            commit = artifact.Commit,
+            key,
+            size = new FileInfo(artifact.ArchivePath).Length,
+            sha256,
+            delta = deltaKey,
+            published_at = DateTimeOffset.UtcNow
+        });
+
+        var body = Encoding.UTF8.GetBytes(JsonConvert.SerializeObject(catalog, Formatting.Indented));
+        res = await Send(client, HttpMethod.Put, catalogKey, Hash(body), () => new ByteArrayContent(body));
+        if (!res.IsSuccessStatusCode)
+            throw new MbssException(ErrorKind.Publish, $"Failed to update {catalogKey} ({(int)res.StatusCode})!");
+    }
+
+    private async Task<HttpResponseMessage> Send(HttpClient client, HttpMethod method, string key,
+        string payloadHash, Func<HttpContent>? content = null)
+    {
+        var uri = new Uri($"{_endpoint}/{_bucket}/{string.Join('/', key.Split('/').Select(Uri.EscapeDataString))}");
+        var host = uri.IsDefaultPort ? uri.Host : $"{uri.Host}:{uri.Port}";
+        var now = DateTime.UtcNow;
+        var amzDate = now.ToString("yyyyMMddTHHmmssZ");
+        var scope = $"{now:yyyyMMdd}/{_region}/s3/aws4_request";
+
+        const string signedHeaders = "host;x-amz-content-sha256;x-amz-date";
+        var canonicalRequest = $"{method.Method}\n{uri.AbsolutePath}\n\nhost:{host}\n" +
+                               $"x-amz-content-sha256:{payloadHash}\nx-amz-date:{amzDate}\n\n" +
+                               $"{signedHeaders}\n{payloadHash}";
+        var stringToSign = $"AWS4-HMAC-SHA256\n{amzDate}\n{scope}\n{Hash(Encoding.UTF8.GetBytes(canonicalRequest))}";
+
+        var signingKey = new[] { $"{now:yyyyMMdd}", _region, "s3", "aws4_request" }
+            .Aggregate(Encoding.UTF8.GetBytes($"AWS4{_secretKey}"),
+                (current, part) => HMACSHA256.HashData(current, Encoding.UTF8.GetBytes(part)));
+        var signature = Convert.ToHexString(HMACSHA256.HashData(signingKey, Encoding.UTF8.GetBytes(stringToSign)))
+            .ToLowerInvariant();
+
+        var req = new HttpRequestMessage(method, uri) { Content = content?.Invoke() };
+        req.Headers.Add("x-amz-content-sha256", payloadHash);
+        req.Headers.Add("x-amz-date", amzDate);
+        req.Headers.TryAddWithoutValidation("Authorization",
+            $"AWS4-HMAC-SHA256 Credential={_accessKey}/{scope}, SignedHeaders={signedHeaders}, Signature={signature}");
+        return await client.SendAsync(req);
+    }
+
+    private static string Hash(byte[] data)
+    {
+        return Convert.ToHexString(SHA256.HashData(data)).ToLowerInvariant();
+    }
+}
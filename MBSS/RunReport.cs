@@ -0,0 +1,173 @@
+﻿using System.Diagnostics;
+using Newtonsoft.Json;
+using Newtonsoft.Json.Converters;
+using Newtonsoft.Json.Linq;
+using Newtonsoft.Json.Serialization;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal class VersionReport
+{
+    [JsonProperty("version")] public string Version { get; set; } = string.Empty;
+    [JsonProperty("manifest")] public string Manifest { get; set; } = string.Empty;
+    [JsonProperty("status")] public string Status { get; set; } = "pending";
+    [JsonProperty("stages")] public Dictionary<string, double> Stages { get; } = new();
+    [JsonProperty("downloaded_bytes")] public long DownloadedBytes { get; set; }
+    [JsonProperty("commit")] public string? Commit { get; set; }
+    [JsonProperty("pushed")] public bool Pushed { get; set; }
+    [JsonProperty("error")] public string? Error { get; set; }
+    [JsonProperty("error_kind"), JsonConverter(typeof(StringEnumConverter), typeof(SnakeCaseNamingStrategy))]
+    public ErrorKind? ErrorKind { get; set; }
+    [JsonProperty("log")] public string? Log { get; set; }
+    [JsonIgnore] public Activity? Activity { get; set; }
+}
+
+internal record RunSnapshot(DateTimeOffset? StartedAt, DateTimeOffset? FinishedAt, JArray Versions, string? Current,
+    string? Stage, double? Seconds);
+
+internal static class RunReport
+{
+    // The daemon's /status reads the report from a request thread while a run keeps updating it
+    private static readonly object Lock = new();
+    private static StageTimer? _activeStage;
+
+    public static List<VersionReport> Versions { get; } = new();
+    public static DateTimeOffset? StartedAt { get; private set; }
+    public static DateTimeOffset? FinishedAt { get; private set; }
+    public static VersionReport? Current { get; private set; }
+
+    public static (string Name, double Seconds)? ActiveStage =>
+        _activeStage == null ? null : (_activeStage.Name, _activeStage.Elapsed);
+
+    public static bool Failed => Versions.Any(x => x.Status == "failed");
+
+    public static void Begin()
+    {
+        End();
+        lock (Lock)
+        {
+            Versions.Clear();
+            StartedAt = DateTimeOffset.UtcNow;
+            FinishedAt = null;
+        }
+    }
+
+    public static VersionReport Start(BeatSaberVersion version)
+    {
+        End();
+
+        GitHubActions.Group($"Version {version.Version}");
+        var report = new VersionReport { Version = version.Version, Manifest = version.Manifest };
+        report.Activity = Telemetry.Source.StartActivity("version")
+            ?.SetTag("mbss.version", version.Version)
+            .SetTag("mbss.manifest", version.Manifest);
+
+        lock (Lock)
+        {
+            Current = report;
+            Versions.Add(report);
+        }
+
+        return report;
+    }
+
+    public static void End()
+    {
+        if (Current != null) GitHubActions.EndGroup();
+        if (Current?.Activity != null)
+        {
+            Current.Activity.SetTag("mbss.status", Current.Status);
+            if (Current.Status == "failed") Current.Activity.SetStatus(ActivityStatusCode.Error, Current.Error);
+            Current.Activity.Dispose();
+        }
+
+        lock (Lock) Current = null;
+    }
+
+    public static RunSnapshot Snapshot()
+    {
+        lock (Lock)
+        {
+            var stage = ActiveStage;
+            return new RunSnapshot(StartedAt, FinishedAt, JArray.FromObject(Versions.Where(x => x.Status != "skipped")),
+                Current?.Version, stage?.Name, stage?.Seconds);
+        }
+    }
+
+    public static IDisposable Stage(string name)
+    {
+        return new StageTimer(Current, name);
+    }
+
+    public static async Task Finish()
+    {
+        End();
+        lock (Lock) FinishedAt = DateTimeOffset.UtcNow;
+
+        Metrics.Record(Versions, Failed);
+        await Metrics.Push();
+        await GitHubActions.Finish(Versions);
+
+        var processed = Versions.Where(x => x.Status != "skipped").ToList();
+        if (processed.Count > 0)
+        {
+            var table = new Table().AddColumns("Version", "Status", "Stages", "Downloaded", "Commit", "Pushed");
+            foreach (var version in processed)
+            {
+                var stages = string.Join(", ", version.Stages.Select(x => $"{x.Key} {x.Value:0}s"));
+                var status = version.Status == "failed"
+                    ? $"[red]failed: {version.Error?.EscapeMarkup()}[/]"
+                    : version.Status;
+                table.AddRow(version.Version.EscapeMarkup(), status, stages, Sizes.Format(version.DownloadedBytes),
+                    version.Commit?[..7] ?? "-", version.Pushed ? "yes" : "no");
+            }
+
+            AnsiConsole.Write(table);
+        }
+
+        var path = Options.Value("--report");
+        if (path == null) return;
+
+        await AtomicFile.WriteAllTextAsync(path, JsonConvert.SerializeObject(Versions, Formatting.Indented));
+        AnsiConsole.MarkupLine($"[green]Wrote run report to {path.EscapeMarkup()}[/]");
+    }
+
+    private class StageTimer : IDisposable
+    {
+        private readonly StageTimer? _parent;
+        private readonly VersionReport? _report;
+        private readonly Activity? _activity;
+        private readonly Stopwatch _stopwatch = Stopwatch.StartNew();
+
+        public StageTimer(VersionReport? report, string name)
+        {
+            lock (Lock)
+            {
+                _parent = _activeStage;
+                _activeStage = this;
+            }
+
+            _report = report;
+            Name = name;
+            _activity = Telemetry.Source.StartActivity(name)
+                ?.SetTag("mbss.version", report?.Version)
+                .SetTag("mbss.manifest", report?.Manifest);
+            Progress.Emit("stage_started", new { version = report?.Version, stage = name });
+        }
+
+        public string Name { get; }
+        public double Elapsed => _stopwatch.Elapsed.TotalSeconds;
+
+        public void Dispose()
+        {
+            lock (Lock) _activeStage = _parent;
+            _activity?.Dispose();
+            var seconds = Elapsed;
+            Progress.Emit("stage_finished", new { version = _report?.Version, stage = Name, seconds });
+
+            if (_report == null) return;
+            lock (Lock) _report.Stages[Name] = _report.Stages.GetValueOrDefault(Name) + seconds;
+        }
+    }
+}
@@ -0,0 +1,85 @@
+﻿using System.Text;
+using System.Text.RegularExpressions;
+using Microsoft.Extensions.FileSystemGlobbing;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal static class SecretScanner
+{
+    private const long MaxTextFileSize = 1024 * 1024;
+    private const int BufferSize = 1024 * 1024;
+
+    private static readonly string[] SecretFiles =
+    {
+        "**/.env", "**/.env.*", "**/config.vdf", "**/loginusers.vdf", "**/ssfn*", "**/account.config",
+        "**/.DepotDownloader/**", "**/*.pem", "**/*.key", "**/.git-credentials"
+    };
+
+    private static readonly Regex[] SecretPatterns =
+    {
+        new(@"gh[pousr]_[A-Za-z0-9]{36}"),
+        new(@"github_pat_[A-Za-z0-9_]{22,}"),
+        new(@"-----BEGIN [A-Z ]*PRIVATE KEY-----")
+    };
+
+    public static async Task Scan(string versionPath)
+    {
+        var matcher = new Matcher(StringComparison.OrdinalIgnoreCase);
+        matcher.AddIncludePatterns(SecretFiles);
+
+        var secrets = Secrets.LongLived.Select(Encoding.UTF8.GetBytes).ToList();
+
+        var findings = new List<string>();
+        foreach (var file in Directory.EnumerateFiles(versionPath, "*", SearchOption.AllDirectories))
+        {
+            var relative = Path.GetRelativePath(versionPath, file);
+            if (matcher.Match(versionPath, file).HasMatches)
+            {
+                findings.Add($"{relative} looks like a credentials file");
+                continue;
+            }
+
+            if (await ContainsAny(file, secrets))
+            {
+                findings.Add($"{relative} contains a configured credential");
+                continue;
+            }
+
+            if (new FileInfo(file).Length > MaxTextFileSize) continue;
+
+            var text = await File.ReadAllTextAsync(file);
+            if (SecretPatterns.Any(pattern => pattern.IsMatch(text))) findings.Add($"{relative} contains a token");
+        }
+
+        if (findings.Count == 0) return;
+
+        foreach (var finding in findings) AnsiConsole.MarkupLine($"[red]{finding.EscapeMarkup()}[/]");
+        throw new MbssException(ErrorKind.ContentCheck,
+            $"Refusing to commit, found {findings.Count} potential secrets!");
+    }
+
+    // Game assets run past what a single array can hold, so files are searched a buffer at a time and the tail of
+    // each buffer is carried over to catch a secret split across two reads
+    private static async Task<bool> ContainsAny(string file, IReadOnlyCollection<byte[]> secrets)
+    {
+        if (secrets.Count == 0) return false;
+
+        var overlap = secrets.Max(x => x.Length) - 1;
+        var buffer = new byte[overlap + BufferSize];
+        var kept = 0;
+
+        await using var stream = File.OpenRead(file);
+        int read;
+        while ((read = await stream.ReadAsync(buffer.AsMemory(kept, BufferSize))) > 0)
+        {
+            var filled = kept + read;
+            if (secrets.Any(secret => buffer.AsSpan(0, filled).IndexOf(secret) != -1)) return true;
+
+            kept = Math.Min(overlap, filled);
+            buffer.AsSpan(filled - kept, kept).CopyTo(buffer);
+        }
+
+        return false;
+    }
+}
@@ -0,0 +1,41 @@
+﻿namespace MBSS;
+
+internal static class Secrets
+{
+    private static readonly string[] SecretEnvs =
+    {
+        "STEAM_PASSWORD", "GITHUB_TOKEN", "MBSS_STEAM_TOTP_SECRET", "MBSS_STEAM_GUARD_CODE",
+        "MBSS_DISCORD_WEBHOOK_URL", "MBSS_SLACK_WEBHOOK_URL", "MBSS_MATRIX_TOKEN",
+        "MBSS_S3_SECRET_ACCESS_KEY", "AWS_SECRET_ACCESS_KEY", "MBSS_NUGET_API_KEY",
+        "OCULUS_ACCESS_TOKEN", "MBSS_DAEMON_TOKEN", "MBSS_NOTIFY_WEBHOOK_URL", "MBSS_ERROR_WEBHOOK_URL", "SENTRY_DSN"
+    };
+
+    // A Steam Guard code is five characters that expire within minutes, far too short to look for in game files
+    private static readonly string[] OneTimeEnvs = { "MBSS_STEAM_GUARD_CODE" };
+
+    // Secrets that don't come from the environment, like the passwords in a Steam account pool
+    private static readonly List<string> Extra = new();
+
+    public static IEnumerable<string> Values => Collect(SecretEnvs);
+
+    public static IEnumerable<string> LongLived => Collect(SecretEnvs.Except(OneTimeEnvs));
+
+    private static IEnumerable<string> Collect(IEnumerable<string> envs)
+    {
+        return envs
+            .Select(Environment.GetEnvironmentVariable)
+            .Concat(Extra)
+            .Where(x => !string.IsNullOrEmpty(x) && x.Length >= 4)
+            .Select(x => x!);
+    }
+
+    public static void Add(string secret)
+    {
+        Extra.Add(secret);
+    }
+
+    public static string Redact(string text)
+    {
+        return Values.Aggregate(text, (current, secret) => current.Replace(secret, "***"));
+    }
+}
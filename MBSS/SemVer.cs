@@ -0,0 +1,91 @@
+﻿namespace MBSS;
+
+// Lenient semver, game versions sometimes have fewer than three components or build metadata like +hotfix1
+internal class SemVer : IComparable<SemVer>
+{
+    private SemVer(string original, int[] core, string[] prerelease, string build)
+    {
+        Original = original;
+        Core = core;
+        Prerelease = prerelease;
+        Build = build;
+    }
+
+    public string Original { get; }
+    public int[] Core { get; }
+    public string[] Prerelease { get; }
+    public string Build { get; }
+
+    public int Major => Core.ElementAtOrDefault(0);
+    public int Minor => Core.ElementAtOrDefault(1);
+    public int Patch => Core.ElementAtOrDefault(2);
+    public bool IsPrerelease => Prerelease.Length > 0;
+
+    public static SemVer Parse(string version)
+    {
+        var buildIndex = version.IndexOf('+');
+        var build = buildIndex == -1 ? string.Empty : version[(buildIndex + 1)..];
+        var rest = buildIndex == -1 ? version : version[..buildIndex];
+
+        var prereleaseIndex = rest.IndexOf('-');
+        var prerelease = prereleaseIndex == -1
+            ? Array.Empty<string>()
+            : rest[(prereleaseIndex + 1)..].Split('.');
+        var core = (prereleaseIndex == -1 ? rest : rest[..prereleaseIndex])
+            .Split('.')
+            .Select(x => int.TryParse(x, out var number) ? number : 0)
+            .ToArray();
+
+        return new SemVer(version, core, prerelease, build);
+    }
+
+    // Git allows + in ref names but plenty of tooling around it doesn't, version.txt keeps the original
+    public static string RefSafe(string version)
+    {
+        return version.Replace('+', '_');
+    }
+
+    public int CompareTo(SemVer? other)
+    {
+        if (other == null) return 1;
+
+        for (var i = 0; i < Math.Max(Core.Length, other.Core.Length); i++)
+        {
+            var result = Core.ElementAtOrDefault(i).CompareTo(other.Core.ElementAtOrDefault(i));
+            if (result != 0) return result;
+        }
+
+        // A prerelease sorts before its release
+        if (Prerelease.Length == 0 || other.Prerelease.Length == 0)
+        {
+            var result = other.Prerelease.Length.CompareTo(Prerelease.Length);
+            if (result != 0) return result;
+        }
+
+        for (var i = 0; i < Math.Min(Prerelease.Length, other.Prerelease.Length); i++)
+        {
+            var result = CompareIdentifier(Prerelease[i], other.Prerelease[i]);
+            if (result != 0) return result;
+        }
+
+        var lengthResult = Prerelease.Length.CompareTo(other.Prerelease.Length);
+        if (lengthResult != 0) return lengthResult;
+
+        // Semver gives build metadata no precedence, but hotfix builds have to land somewhere consistent
+        return string.CompareOrdinal(Build, other.Build);
+    }
+
+    public override string ToString()
+    {
+        return Original;
+    }
+
+    private static int CompareIdentifier(string a, string b)
+    {
+        var aNumeric = int.TryParse(a, out var aNumber);
+        var bNumeric = int.TryParse(b, out var bNumber);
+        if (aNumeric && bNumeric) return aNumber.CompareTo(bNumber);
+        if (aNumeric != bNumeric) return aNumeric ? -1 : 1;
+        return string.CompareOrdinal(a, b);
+    }
+}
@@ -0,0 +1,93 @@
+﻿namespace MBSS;
+
+// npm style ranges: ^1.29, ~1.29.1, 1.29.x, >=1.29 <1.31, and alternatives joined by ||
+internal class SemVerRange
+{
+    private readonly List<List<(string Op, SemVer Version)>> _sets;
+
+    private SemVerRange(List<List<(string Op, SemVer Version)>> sets)
+    {
+        _sets = sets;
+    }
+
+    public static SemVerRange Parse(string range)
+    {
+        var sets = range.Split("||")
+            .Select(set => set.Split(' ', StringSplitOptions.RemoveEmptyEntries).SelectMany(ParseComparator).ToList())
+            .ToList();
+        return new SemVerRange(sets);
+    }
+
+    public bool IsSatisfiedBy(SemVer version)
+    {
+        return _sets.Any(set =>
+        {
+            // Prereleases only match when the range asks for one on the same version core
+            if (version.IsPrerelease && !set.Any(x => x.Version.IsPrerelease && SameCore(x.Version, version)))
+                return false;
+
+            return set.All(x => Compare(version, x.Version) is var result && x.Op switch
+            {
+                ">" => result > 0,
+                ">=" => result >= 0,
+                "<" => result < 0,
+                "<=" => result <= 0,
+                _ => result == 0
+            });
+        });
+    }
+
+    // Build metadata doesn't take part in matching, 1.29.4 is satisfied by 1.29.4+hotfix1
+    private static int Compare(SemVer a, SemVer b)
+    {
+        return SemVer.Parse(a.Original.Split('+')[0]).CompareTo(SemVer.Parse(b.Original.Split('+')[0]));
+    }
+
+    private static bool SameCore(SemVer a, SemVer b)
+    {
+        return a.Major == b.Major && a.Minor == b.Minor && a.Patch == b.Patch;
+    }
+
+    private static IEnumerable<(string Op, SemVer Version)> ParseComparator(string comparator)
+    {
+        var op = new[] { ">=", "<=", ">", "<", "=", "^", "~" }.FirstOrDefault(comparator.StartsWith) ?? string.Empty;
+        var version = comparator[op.Length..].TrimStart('v');
+
+        // Missing or wildcard components widen the range to everything below them
+        var parts = version.Split('-', '+')[0].Split('.');
+        var specified = parts.TakeWhile(x => x.Length > 0 && x != "x" && x != "X" && x != "*").Count();
+        if (specified == 0) return new[] { (">=", SemVer.Parse("0.0.0")) };
+
+        var numbers = parts.Take(specified).Select(int.Parse).Concat(new[] { 0, 0, 0 }).Take(3).ToArray();
+        var lower = specified == parts.Length && specified >= 3
+            ? SemVer.Parse(version)
+            : SemVer.Parse(string.Join('.', numbers));
+
+        switch (op)
+        {
+            case "^":
+            {
+                // The first non-zero component is the one allowed to stay fixed
+                var fixedIndex = numbers[0] > 0 || specified == 1 ? 0 : numbers[1] > 0 || specified == 2 ? 1 : 2;
+                return new[] { (">=", lower), ("<", Upper(numbers, fixedIndex)) };
+            }
+            case "~":
+                return new[] { (">=", lower), ("<", Upper(numbers, specified >= 2 ? 1 : 0)) };
+            case "" or "=" when specified < 3:
+                return new[] { (">=", lower), ("<", Upper(numbers, specified - 1)) };
+            case ">" when specified < 3:
+                return new[] { (">=", Upper(numbers, specified - 1)) };
+            case "<=" when specified < 3:
+                return new[] { ("<", Upper(numbers, specified - 1)) };
+            default:
+                return new[] { (op == string.Empty ? "=" : op, lower) };
+        }
+    }
+
+    private static SemVer Upper(int[] numbers, int index)
+    {
+        var upper = numbers.Take(index + 1).ToArray();
+        upper[index]++;
+        return SemVer.Parse(string.Join('.', upper.Concat(new[] { 0, 0, 0 }).Take(3)) + "-0");
+    }
+}
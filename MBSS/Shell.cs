@@ -0,0 +1,26 @@
+﻿using System.Diagnostics;
+
+namespace MBSS;
+
+internal static class Shell
+{
+    public static async Task<int> Run(string command, IDictionary<string, string> env)
+    {
+        var process = new Process
+        {
+            StartInfo =
+            {
+                FileName = OperatingSystem.IsWindows() ? "cmd.exe" : "/bin/sh",
+                UseShellExecute = false
+            }
+        };
+
+        process.StartInfo.ArgumentList.Add(OperatingSystem.IsWindows() ? "/c" : "-c");
+        process.StartInfo.ArgumentList.Add(command);
+        foreach (var (key, value) in env) process.StartInfo.Environment[key] = value;
+
+        process.Start();
+        await process.WaitForExitAsync();
+        return process.ExitCode;
+    }
+}
@@ -0,0 +1,49 @@
+﻿using System.Globalization;
+
+namespace MBSS;
+
+internal static class Sizes
+{
+    private static readonly string[] Units = { "B", "KB", "MB", "GB", "TB" };
+
+    public static string Format(long bytes, bool signed = false)
+    {
+        var sign = signed && bytes > 0 ? "+" : bytes < 0 ? "-" : string.Empty;
+        double size = Math.Abs(bytes);
+        var unit = 0;
+        while (size >= 1024 && unit < Units.Length - 1)
+        {
+            size /= 1024;
+            unit++;
+        }
+
+        return $"{sign}{size:0.#} {Units[unit]}";
+    }
+
+    // Accepts plain bytes or a K/M/G suffix in powers of 1024, matching what Format prints
+    public static long Parse(string value, string description)
+    {
+        var suffix = char.ToUpperInvariant(value.TrimEnd('B', 'b')[^1]);
+        var multiplier = suffix switch
+        {
+            'K' => 1024L,
+            'M' => 1024L * 1024,
+            'G' => 1024L * 1024 * 1024,
+            _ => 1L
+        };
+
+        // 1.5GB means the same on every host, whatever the decimal separator of its culture is
+        var number = (multiplier == 1 ? value.TrimEnd('B', 'b') : value.TrimEnd('B', 'b')[..^1]).Trim();
+        if (!double.TryParse(number, NumberStyles.Float, CultureInfo.InvariantCulture, out var amount) || amount <= 0)
+            throw new MbssException(ErrorKind.Config, $"Invalid {description} {value}!");
+
+        return (long)(amount * multiplier);
+    }
+
+    public static long OfDirectory(string path)
+    {
+        return Directory.Exists(path)
+            ? Directory.EnumerateFiles(path, "*", SearchOption.AllDirectories).Sum(x => new FileInfo(x).Length)
+            : 0;
+    }
+}
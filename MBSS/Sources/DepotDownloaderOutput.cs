@@ -0,0 +1,87 @@
+﻿using System.Globalization;
+using System.Text.RegularExpressions;
+
+namespace MBSS.Sources;
+
+internal enum DepotDownloaderError
+{
+    RateLimited,
+    InvalidPassword,
+    ManifestNotFound,
+    NoAccess
+}
+
+internal static class DepotDownloaderOutput
+{
+    private static readonly Regex ProgressRegex = new(@"^\s*(?<percent>\d+(\.\d+)?)%\s+(?<file>.+?)\s*$");
+    private static readonly Regex TotalRegex = new(@"Total downloaded: (?<bytes>\d+) bytes");
+
+    // Matched against every line, the first error seen decides how a failed run is reported and retried
+    private static readonly (Regex Pattern, DepotDownloaderError Error)[] Signatures =
+    {
+        (new Regex(@"RateLimitExceeded|rate limit", RegexOptions.IgnoreCase), DepotDownloaderError.RateLimited),
+        (new Regex(@"InvalidPassword|invalid password", RegexOptions.IgnoreCase),
+            DepotDownloaderError.InvalidPassword),
+        (new Regex(@"manifest.*not (be )?found|unable to download manifest|error downloading manifest",
+            RegexOptions.IgnoreCase), DepotDownloaderError.ManifestNotFound),
+        (new Regex(@"is not available from this account|AccessDenied", RegexOptions.IgnoreCase),
+            DepotDownloaderError.NoAccess)
+    };
+
+    public static bool TryParseProgress(string line, out double percent, out string file)
+    {
+        var match = ProgressRegex.Match(line);
+        percent = match.Success ? double.Parse(match.Groups["percent"].Value, CultureInfo.InvariantCulture) : 0;
+        file = match.Success ? match.Groups["file"].Value : string.Empty;
+        return match.Success;
+    }
+
+    public static long? ParseDownloadedBytes(string line)
+    {
+        var match = TotalRegex.Match(line);
+        return match.Success ? long.Parse(match.Groups["bytes"].Value, CultureInfo.InvariantCulture) : null;
+    }
+
+    public static DepotDownloaderError? Classify(string line)
+    {
+        foreach (var (pattern, error) in Signatures)
+            if (pattern.IsMatch(line))
+                return error;
+
+        return null;
+    }
+
+    public static string Describe(DepotDownloaderError error)
+    {
+        return error switch
+        {
+            DepotDownloaderError.RateLimited => "Steam rate limited the login, try again later",
+            DepotDownloaderError.InvalidPassword =>
+                "Steam rejected the login, check STEAM_USERNAME and STEAM_PASSWORD",
+            DepotDownloaderError.ManifestNotFound => "Steam could not find the manifest, check versions.json",
+            DepotDownloaderError.NoAccess => "The Steam account does not own Beat Saber",
+            _ => error.ToString()
+        };
+    }
+}
+
+internal class DepotDownloaderException : MbssException
+{
+    public DepotDownloaderException(DepotDownloaderError error) : base(KindOf(error),
+        $"{DepotDownloaderOutput.Describe(error)}!")
+    {
+        Error = error;
+    }
+
+    public DepotDownloaderError Error { get; }
+
+    private static ErrorKind KindOf(DepotDownloaderError error)
+    {
+        return error switch
+        {
+            DepotDownloaderError.RateLimited => ErrorKind.RateLimited,
+            DepotDownloaderError.ManifestNotFound => ErrorKind.ManifestNotFound,
+            _ => ErrorKind.SteamAuth
+        };
+    }
+}
@@ -0,0 +1,282 @@
+﻿using System.Diagnostics;
+using System.Text;
+using MBSS.Tools;
+using Spectre.Console;
+
+namespace MBSS.Sources;
+
+internal class DepotDownloaderSource : IDepotSource
+{
+    public bool RequiresSteam => true;
+    public bool RequiresDepotDownloader => true;
+
+    public async Task<string> Fetch(BeatSaberVersion version, string downloadPath)
+    {
+        // An existing download directory means a previous run was interrupted, let DepotDownloader repair it
+        var validate = Directory.Exists(downloadPath);
+        if (validate)
+            AnsiConsole.MarkupLine($"[yellow]Found partial download for version {version.Version}, validating...[/]");
+
+        var exitCode = await DownloadWithRetries(version, downloadPath, validate);
+        if (exitCode != 0 && !validate)
+        {
+            AnsiConsole.MarkupLine(
+                $"[yellow]DepotDownloader exited with code {exitCode}, retrying with validation...[/]");
+            exitCode = await DownloadWithRetries(version, downloadPath, true);
+        }
+
+        if (exitCode != 0)
+            throw new MbssException(ErrorKind.Download,
+                $"DepotDownloader failed to download version {version.Version}!");
+
+        return downloadPath;
+    }
+
+    public async Task Preflight()
+    {
+        if (SteamAccounts.AnonymousOnly) return;
+
+        // Every pooled account is checked, a broken one would otherwise only show up in the middle of a backfill
+        foreach (var account in SteamAccounts.All)
+        {
+            using var checkPath = new TempPath();
+            var exitCode = await RunDepotDownloader(account,
+                $"-app 620980 -depot 620981 -manifest-only -dir \"{checkPath.Path}\"");
+
+            if (exitCode != 0)
+                throw new MbssException(ErrorKind.SteamAuth,
+                    $"Failed to log in to Steam as {account.Username} and fetch the Beat Saber manifest!");
+        }
+    }
+
+    public async Task<string> FetchManifestListing(BeatSaberVersion version, string outputPath)
+    {
+        var depotArgs = ManifestArgs(version) + $" -manifest-only -dir \"{outputPath}\"";
+        var exitCode = await RunDepotDownloader(version.IsAnonymous ? null : SteamAccounts.Acquire(), depotArgs);
+        var listing = Path.Combine(outputPath, $"manifest_{version.DepotId}_{version.Manifest}.txt");
+        if (exitCode != 0 || !File.Exists(listing))
+            throw new MbssException(ErrorKind.ManifestNotFound,
+                $"Failed to fetch the manifest of version {version.Version}!");
+
+        return listing;
+    }
+
+    // Just the files VersionCheck reads the game version from, for builds nobody has named yet
+    public async Task<string> FetchVersionFiles(BeatSaberVersion version, string outputPath)
+    {
+        using var fileListPath = new TempPath(".txt");
+        await File.WriteAllTextAsync(fileListPath.Path, "BeatSaberVersion.txt\nBeat Saber_Data/globalgamemanagers");
+
+        var depotArgs = ManifestArgs(version) + $" -dir \"{outputPath}\" -filelist \"{fileListPath.Path}\"";
+        var exitCode = await RunDepotDownloader(version.IsAnonymous ? null : SteamAccounts.Acquire(), depotArgs);
+        if (exitCode != 0)
+            throw new MbssException(ErrorKind.Download, $"Failed to download the version files of {version.Manifest}!");
+
+        return outputPath;
+    }
+
+    private static async Task<int> DownloadWithRetries(BeatSaberVersion version, string downloadPath, bool validate)
+    {
+        for (var attempt = 0;; attempt++)
+        {
+            var account = version.IsAnonymous ? null : SteamAccounts.Acquire();
+            try
+            {
+                return await DownloadVersion(account, version, downloadPath, validate);
+            }
+            catch (TimeoutException e) when (attempt < Watchdog.Retries)
+            {
+                // Whatever made it to disk before the hang is kept and validated
+                AnsiConsole.MarkupLine($"[yellow]{e.Message.EscapeMarkup()} Retrying...[/]");
+                validate = true;
+            }
+            catch (DepotDownloaderException e) when (e.Error == DepotDownloaderError.RateLimited &&
+                                                     attempt < Watchdog.Retries)
+            {
+                // Logging in again right away only extends the rate limit, unless another account can take over
+                validate = true;
+                if (account != null && SteamAccounts.Throttled(account)) continue;
+
+                var delay = TimeSpan.FromMinutes(5 * (attempt + 1));
+                AnsiConsole.MarkupLine($"[yellow]{e.Message.EscapeMarkup()} Retrying in {delay}...[/]");
+                await Task.Delay(delay);
+            }
+        }
+    }
+
+    private static async Task<int> DownloadVersion(SteamAccount? account, BeatSaberVersion version,
+        string downloadPath, bool validate)
+    {
+        var depotArgs = ManifestArgs(version) + $" -dir \"{downloadPath}\"";
+        if (validate) depotArgs += " -validate";
+
+        var fileList = FileFilter.FromEnvironment().ToDepotFileList();
+        using var fileListPath = new TempPath(".txt");
+        if (fileList != null)
+        {
+            await File.WriteAllTextAsync(fileListPath.Path, fileList);
+            depotArgs += $" -filelist \"{fileListPath.Path}\"";
+        }
+
+        // Progress lines are folded into a bar on terminals, anywhere else they're kept as plain logs
+        var exitCode = 0;
+        if (AnsiConsole.Profile.Capabilities.Interactive && !Progress.Enabled)
+        {
+            await AnsiConsole.Progress().StartAsync(async ctx =>
+            {
+                var task = ctx.AddTask($"Downloading {version.Version}");
+                exitCode = await RunDepotDownloader(account, depotArgs, percent => task.Value = percent);
+                task.Value = task.MaxValue;
+            });
+        }
+        else
+        {
+            exitCode = await RunDepotDownloader(account, depotArgs);
+        }
+
+        return exitCode;
+    }
+
+    private static string ManifestArgs(BeatSaberVersion version)
+    {
+        var args = $"-app 620980 -depot {version.DepotId} -manifest \"{version.Manifest}\"";
+        if (!string.IsNullOrEmpty(version.Beta)) args += $" -beta \"{version.Beta}\"";
+        if (!string.IsNullOrEmpty(version.BetaPassword)) args += $" -betapassword \"{version.BetaPassword}\"";
+        if (version.Platform != null) args += $" -os {version.PlatformName}";
+        return args;
+    }
+
+    // Without an account DepotDownloader logs in anonymously
+    private static async Task<int> RunDepotDownloader(SteamAccount? account, string depotArgs,
+        Action<double>? onProgress = null)
+    {
+        if (account != null) depotArgs += $" -remember-password -username \"{account.Username}\"";
+        depotArgs += DownloadLimits.Arguments;
+
+        var depotDownloader = new Process
+        {
+            StartInfo =
+            {
+                FileName = Path.GetFullPath(GitHubTool.DepotDownloader.ExecutablePath),
+                Arguments = depotArgs,
+                RedirectStandardInput = true,
+                RedirectStandardOutput = true,
+                RedirectStandardError = true,
+                UseShellExecute = false
+            }
+        };
+
+        // DepotDownloader remembers logins in isolated storage below the local application data directory. That's
+        // XDG_DATA_HOME or HOME on Linux and HOME on macOS. On Windows it's a known folder, which is expanded from
+        // USERPROFILE, so all of them point at the session directory. The working directory goes there as well, in
+        // case a DepotDownloader version keeps its session in the current directory
+        if (account != null)
+        {
+            var sessionDirectory = Path.GetFullPath(account.SessionDirectory);
+            Directory.CreateDirectory(sessionDirectory);
+            foreach (var variable in new[] { "HOME", "XDG_DATA_HOME", "USERPROFILE", "LOCALAPPDATA", "APPDATA" })
+                depotDownloader.StartInfo.Environment[variable] = sessionDirectory;
+            depotDownloader.StartInfo.WorkingDirectory = sessionDirectory;
+        }
+        DownloadLimits.Apply(depotDownloader.StartInfo);
+
+        try
+        {
+            depotDownloader.Start();
+        }
+        catch (System.ComponentModel.Win32Exception e) when (DownloadLimits.Bandwidth != null)
+        {
+            throw new MbssException(ErrorKind.Config, $"MBSS_BANDWIDTH_LIMIT requires trickle: {e.Message}", e);
+        }
+
+        using var log = ChildLog.Open("DepotDownloader");
+        using var watchdog =
+            Watchdog.ForStage(depotDownloader, "DepotDownloader", "DOWNLOAD", TimeSpan.FromMinutes(15));
+        depotDownloader.ErrorDataReceived += (_, e) =>
+        {
+            if (e.Data == null) return;
+
+            watchdog.Output(e.Data);
+            log.Write("stderr", e.Data);
+        };
+        depotDownloader.BeginErrorReadLine();
+
+        // Prompts don't end with a newline, so the output has to be scanned as it arrives
+        var line = new StringBuilder();
+        var buffer = new char[4096];
+        var attempts = 0;
+        var passwordSent = false;
+        DepotDownloaderError? error = null;
+        int read;
+        while ((read = await depotDownloader.StandardOutput.ReadAsync(buffer, 0, buffer.Length)) > 0)
+        {
+            watchdog.Kick();
+            for (var i = 0; i < read; i++)
+            {
+                line.Append(buffer[i]);
+                if (buffer[i] != '\n') continue;
+
+                var complete = line.ToString();
+                line.Clear();
+                watchdog.Output(complete);
+
+                if (DepotDownloaderOutput.TryParseProgress(complete, out var percent, out var file))
+                {
+                    Progress.Emit("download_progress", new { percent, file });
+                    if (onProgress != null)
+                    {
+                        onProgress(percent);
+                        continue;
+                    }
+                }
+
+                error ??= DepotDownloaderOutput.Classify(complete);
+                if (DepotDownloaderOutput.ParseDownloadedBytes(complete) is { } bytes && RunReport.Current != null)
+                    RunReport.Current.DownloadedBytes = bytes;
+                log.Write("stdout", complete);
+            }
+
+            var pending = line.ToString();
+            if (pending.Contains("Enter account password", StringComparison.OrdinalIgnoreCase))
+            {
+                AnsiConsole.WriteLine(pending);
+                line.Clear();
+
+                // The password is passed over stdin so it never shows up in process listings
+                if (passwordSent || account == null)
+                {
+                    depotDownloader.Kill(true);
+                    throw new DepotDownloaderException(DepotDownloaderError.InvalidPassword);
+                }
+
+                await depotDownloader.StandardInput.WriteLineAsync(account.Password);
+                passwordSent = true;
+                continue;
+            }
+
+            if (!SteamGuard.IsPrompt(pending)) continue;
+            AnsiConsole.WriteLine(pending);
+            line.Clear();
+
+            var code = account == null ? null : SteamGuard.GetCode(account, attempts++);
+            if (code == null)
+            {
+                depotDownloader.Kill(true);
+                throw new MbssException(ErrorKind.SteamAuth,
+                    "Steam Guard 2FA code required! Set MBSS_STEAM_TOTP_SECRET or MBSS_STEAM_GUARD_CODE.");
+            }
+
+            await depotDownloader.StandardInput.WriteLineAsync(code);
+        }
+
+        if (line.Length > 0) log.Write("stdout", line.ToString());
+
+        await depotDownloader.WaitForExitAsync();
+        log.Write("mbss", $"Exited with code {depotDownloader.ExitCode}");
+        watchdog.ThrowIfTripped();
+
+        // Known failures won't go away by validating and retrying, unlike a dropped connection
+        if (depotDownloader.ExitCode != 0 && error != null) throw new DepotDownloaderException(error.Value);
+        return depotDownloader.ExitCode;
+    }
+}
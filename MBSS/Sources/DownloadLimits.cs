@@ -0,0 +1,40 @@
+﻿using System.Diagnostics;
+
+namespace MBSS.Sources;
+
+internal static class DownloadLimits
+{
+    // Fewer parallel chunk downloads leave room on a connection shared with other services
+    public static string Arguments
+    {
+        get
+        {
+            var args = string.Empty;
+            if (int.TryParse(Environment.GetEnvironmentVariable("MBSS_MAX_DOWNLOADS"), out var downloads))
+                args += $" -max-downloads {downloads}";
+            if (int.TryParse(Environment.GetEnvironmentVariable("MBSS_MAX_SERVERS"), out var servers))
+                args += $" -max-servers {servers}";
+            return args;
+        }
+    }
+
+    // Bytes per second, DepotDownloader has no limit of its own so it runs under trickle
+    public static long? Bandwidth
+    {
+        get
+        {
+            var value = Environment.GetEnvironmentVariable("MBSS_BANDWIDTH_LIMIT");
+            return string.IsNullOrEmpty(value) ? null : Sizes.Parse(value, "MBSS_BANDWIDTH_LIMIT");
+        }
+    }
+
+    public static void Apply(ProcessStartInfo startInfo)
+    {
+        if (Bandwidth is not { } bandwidth) return;
+        if (OperatingSystem.IsWindows())
+            throw new MbssException(ErrorKind.Config, "MBSS_BANDWIDTH_LIMIT is not supported on Windows!");
+
+        startInfo.Arguments = $"-s -d {Math.Max(1, bandwidth / 1024)} \"{startInfo.FileName}\" {startInfo.Arguments}";
+        startInfo.FileName = Environment.GetEnvironmentVariable("MBSS_TRICKLE_PATH") ?? "trickle";
+    }
+}
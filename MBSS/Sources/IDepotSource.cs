@@ -0,0 +1,12 @@
+﻿namespace MBSS.Sources;
+
+internal interface IDepotSource
+{
+    bool RequiresSteam { get; }
+
+    bool RequiresDepotDownloader { get; }
+
+    Task Preflight();
+
+    Task<string> Fetch(BeatSaberVersion version, string downloadPath);
+}
@@ -0,0 +1,26 @@
+﻿namespace MBSS.Sources;
+
+internal class ImportSource : IDepotSource
+{
+    private readonly string _path;
+
+    public ImportSource(string path)
+    {
+        _path = Path.GetFullPath(path);
+    }
+
+    public bool RequiresSteam => false;
+    public bool RequiresDepotDownloader => false;
+
+    public Task Preflight()
+    {
+        if (!Directory.Exists(_path))
+            throw new MbssException(ErrorKind.Config, $"Game directory {_path} does not exist!");
+        return Task.CompletedTask;
+    }
+
+    public Task<string> Fetch(BeatSaberVersion version, string downloadPath)
+    {
+        return Task.FromResult(_path);
+    }
+}
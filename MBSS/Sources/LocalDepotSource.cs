@@ -0,0 +1,30 @@
+﻿namespace MBSS.Sources;
+
+internal class LocalDepotSource : IDepotSource
+{
+    private readonly string _root;
+
+    public LocalDepotSource(string root)
+    {
+        _root = root;
+    }
+
+    public bool RequiresSteam => false;
+    public bool RequiresDepotDownloader => false;
+
+    public Task Preflight()
+    {
+        if (!Directory.Exists(_root))
+            throw new MbssException(ErrorKind.Config, $"Local depot directory {_root} does not exist!");
+        return Task.CompletedTask;
+    }
+
+    public Task<string> Fetch(BeatSaberVersion version, string downloadPath)
+    {
+        var path = Path.GetFullPath(Path.Combine(_root, version.Version));
+        if (!Directory.Exists(path))
+            throw new MbssException(ErrorKind.Download, $"Local depot for version {version.Version} does not exist!");
+
+        return Task.FromResult(path);
+    }
+}
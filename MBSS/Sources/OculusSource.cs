@@ -0,0 +1,104 @@
+﻿using System.Net;
+using MBSS.Tools;
+using Spectre.Console;
+
+namespace MBSS.Sources;
+
+// Oculus builds come as one archive per binary id, fetched with the access token of an account that owns the game
+internal class OculusSource : IDepotSource
+{
+    // Game builds are far larger than the tool archives SafeZip is tuned for
+    private const long MaxBuildBytes = 64L * 1024 * 1024 * 1024;
+
+    private readonly HttpClient _client;
+
+    public OculusSource(HttpClient client)
+    {
+        _client = client;
+    }
+
+    public bool RequiresSteam => false;
+    public bool RequiresDepotDownloader => false;
+
+    private static string Token
+    {
+        get
+        {
+            Credentials.Load(new[] { "OCULUS_ACCESS_TOKEN" });
+            var token = Environment.GetEnvironmentVariable("OCULUS_ACCESS_TOKEN");
+            return !string.IsNullOrEmpty(token)
+                ? token
+                : throw new MbssException(ErrorKind.Config, "Oculus versions require OCULUS_ACCESS_TOKEN!");
+        }
+    }
+
+    public Task Preflight()
+    {
+        _ = Token;
+        return Task.CompletedTask;
+    }
+
+    public async Task<string> Fetch(BeatSaberVersion version, string downloadPath)
+    {
+        if (string.IsNullOrEmpty(version.OculusBinaryId))
+            throw new MbssException(ErrorKind.Config, $"Version {version.Version} has no oculus_binary_id!");
+
+        var url = "https://securecdn.oculus.com/binaries/download/" +
+                  $"?id={Uri.EscapeDataString(version.OculusBinaryId)}&access_token={Uri.EscapeDataString(Token)}";
+        using var res = await _client.GetAsync(url, HttpCompletionOption.ResponseHeadersRead);
+        if (res.StatusCode is HttpStatusCode.Unauthorized or HttpStatusCode.Forbidden)
+            throw new MbssException(ErrorKind.Config,
+                $"OCULUS_ACCESS_TOKEN cannot download binary {version.OculusBinaryId} ({(int)res.StatusCode})!");
+        if (!res.IsSuccessStatusCode)
+            throw new MbssException(ErrorKind.Download,
+                $"Failed to download Oculus binary {version.OculusBinaryId} ({(int)res.StatusCode})!");
+
+        using var archive = new TempPath(".zip");
+        var length = res.Content.Headers.ContentLength;
+
+        async Task Download(Action<long>? onProgress)
+        {
+            await using var input = await res.Content.ReadAsStreamAsync();
+            await using var output = File.Create(archive.Path);
+            var buffer = new byte[81920];
+            long total = 0;
+            var reported = -1L;
+            int read;
+            while ((read = await input.ReadAsync(buffer)) > 0)
+            {
+                await output.WriteAsync(buffer.AsMemory(0, read));
+                total += read;
+                onProgress?.Invoke(read);
+
+                // Whole percents only, every buffer would flood the progress events
+                var percent = length is > 0 ? total * 100 / length.Value : 0;
+                if (percent == reported) continue;
+                reported = percent;
+                Progress.Emit("download_progress", new { percent });
+            }
+        }
+
+        // Same as DepotDownloader, a bar on terminals and nothing but the progress events anywhere else
+        if (AnsiConsole.Profile.Capabilities.Interactive && !Progress.Enabled)
+        {
+            await AnsiConsole.Progress().StartAsync(async ctx =>
+            {
+                var task = ctx.AddTask($"Downloading {version.Version}", maxValue: length ?? 1);
+                task.IsIndeterminate = length == null;
+                await Download(read => task.Increment(read));
+                task.Value = task.MaxValue;
+            });
+        }
+        else
+        {
+            await Download(null);
+        }
+
+        if (RunReport.Current != null) RunReport.Current.DownloadedBytes = new FileInfo(archive.Path).Length;
+
+        // Archives can't be resumed like a DepotDownloader download, a leftover directory is just stale
+        if (Directory.Exists(downloadPath)) Directory.Delete(downloadPath, true);
+        SafeZip.Extract(archive.Path, downloadPath, ErrorKind.Download, MaxBuildBytes);
+        return downloadPath;
+    }
+}
@@ -0,0 +1,337 @@
+﻿using System.Security.Cryptography;
+using SteamKit2;
+using SteamKit2.Authentication;
+using SteamKit2.CDN;
+using Spectre.Console;
+
+namespace MBSS.Sources;
+
+// Downloads depots straight from Steam's CDN, without DepotDownloader or the runtime it needs
+internal class SteamKitSource : IDepotSource
+{
+    private const uint AppId = 620980;
+
+    public bool RequiresSteam => true;
+    public bool RequiresDepotDownloader => false;
+
+    public async Task Preflight()
+    {
+        if (SteamAccounts.AnonymousOnly) return;
+
+        // Getting the depot key proves the account owns the game, not just that the password is right
+        foreach (var account in SteamAccounts.All)
+        {
+            using var session = await SteamSession.Open(account);
+            await session.DepotKey(620981);
+        }
+    }
+
+    public async Task<string> Fetch(BeatSaberVersion version, string downloadPath)
+    {
+        if (!string.IsNullOrEmpty(version.BetaPassword))
+            throw new MbssException(ErrorKind.Config,
+                $"Version {version.Version} is on a password protected beta, which needs the depotdownloader source!");
+
+        for (var attempt = 0;; attempt++)
+        {
+            var account = version.IsAnonymous ? null : SteamAccounts.Acquire();
+            try
+            {
+                using var session = await SteamSession.Open(account);
+                await Download(session, version, downloadPath);
+                return downloadPath;
+            }
+            catch (MbssException e) when (e.Kind == ErrorKind.RateLimited && attempt < Watchdog.Retries)
+            {
+                // Logging in again right away only extends the rate limit, unless another account can take over
+                if (account != null && SteamAccounts.Throttled(account)) continue;
+
+                var delay = TimeSpan.FromMinutes(5 * (attempt + 1));
+                AnsiConsole.MarkupLine($"[yellow]{e.Message.EscapeMarkup()} Retrying in {delay}...[/]");
+                await Task.Delay(delay);
+            }
+        }
+    }
+
+    private static async Task Download(SteamSession session, BeatSaberVersion version, string downloadPath)
+    {
+        var depotId = uint.Parse(version.DepotId);
+        var depotKey = await session.DepotKey(depotId);
+        var manifest = await session.Manifest(depotId, ulong.Parse(version.Manifest), version.Beta, depotKey);
+
+        var filter = FileFilter.FromEnvironment();
+        var files = (manifest.Files ?? new List<DepotManifest.FileData>())
+            .Where(x => !x.Flags.HasFlag(EDepotFileFlag.Directory))
+            .Select(x => (Name: x.FileName.Replace('\\', '/'), File: x))
+            .Where(x => filter.IsIncluded(downloadPath, Path.Combine(downloadPath, x.Name)))
+            .ToList();
+
+        var total = Math.Max(files.Sum(x => (long)x.File.TotalSize), 1);
+        long done = 0;
+        long downloaded = 0;
+
+        async Task DownloadAll(Action<double>? onProgress)
+        {
+            foreach (var (name, file) in files)
+            {
+                var path = Path.Combine(downloadPath, name);
+                Directory.CreateDirectory(Path.GetDirectoryName(path)!);
+
+                // Files a previous run already finished are kept, so an interrupted download picks up where it was
+                if (!await Matches(path, file))
+                {
+                    using (var handle = File.OpenHandle(path, FileMode.Create, FileAccess.Write))
+                    {
+                        await Parallel.ForEachAsync(file.Chunks, Parallelism.Options, async (chunk, token) =>
+                        {
+                            var data = await session.Chunk(depotId, chunk, depotKey);
+                            await RandomAccess.WriteAsync(handle, data, (long)chunk.Offset, token);
+                            Interlocked.Add(ref downloaded, chunk.CompressedLength);
+                        });
+                    }
+
+                    if (!await Matches(path, file))
+                        throw new MbssException(ErrorKind.Download,
+                            $"{name} of version {version.Version} doesn't match its manifest!");
+                }
+
+                if (file.Flags.HasFlag(EDepotFileFlag.Executable) && !OperatingSystem.IsWindows())
+                    File.SetUnixFileMode(path, File.GetUnixFileMode(path) | UnixFileMode.UserExecute |
+                                               UnixFileMode.GroupExecute | UnixFileMode.OtherExecute);
+
+                if (RunReport.Current != null) RunReport.Current.DownloadedBytes = Interlocked.Read(ref downloaded);
+                done += (long)file.TotalSize;
+                var percent = Math.Round(done * 100.0 / total, 2);
+                Progress.Emit("download_progress", new { percent, file = name });
+                onProgress?.Invoke(percent);
+            }
+        }
+
+        // Same as DepotDownloader, a bar on terminals and nothing but the progress events anywhere else
+        if (AnsiConsole.Profile.Capabilities.Interactive && !Progress.Enabled)
+        {
+            await AnsiConsole.Progress().StartAsync(async ctx =>
+            {
+                var task = ctx.AddTask($"Downloading {version.Version}");
+                await DownloadAll(percent => task.Value = percent);
+                task.Value = task.MaxValue;
+            });
+        }
+        else
+        {
+            await DownloadAll(null);
+        }
+    }
+
+    private static async Task<bool> Matches(string path, DepotManifest.FileData file)
+    {
+        if (!File.Exists(path) || new FileInfo(path).Length != (long)file.TotalSize) return false;
+
+        await using var stream = File.OpenRead(path);
+        return (await SHA1.HashDataAsync(stream)).AsSpan().SequenceEqual(file.FileHash);
+    }
+
+    private sealed class SteamSession : IDisposable
+    {
+        private const int MaxAttempts = 5;
+        private static readonly TimeSpan Timeout = TimeSpan.FromSeconds(30);
+
+        private readonly SteamClient _client = new();
+        private readonly CallbackManager _manager;
+        private readonly SteamUser _user;
+        private readonly SteamApps _apps;
+        private readonly SteamContent _content;
+        private readonly Client _cdn;
+        private readonly CancellationTokenSource _stop = new();
+        private readonly Task _callbacks;
+
+        private readonly TaskCompletionSource _connected = new(TaskCreationOptions.RunContinuationsAsynchronously);
+        private readonly TaskCompletionSource<SteamUser.LoggedOnCallback> _loggedOn =
+            new(TaskCreationOptions.RunContinuationsAsynchronously);
+
+        private List<Server> _servers = new();
+
+        private SteamSession()
+        {
+            _manager = new CallbackManager(_client);
+            _user = _client.GetHandler<SteamUser>()!;
+            _apps = _client.GetHandler<SteamApps>()!;
+            _content = _client.GetHandler<SteamContent>()!;
+            _cdn = new Client(_client);
+
+            _manager.Subscribe<SteamClient.ConnectedCallback>(_ => _connected.TrySetResult());
+            _manager.Subscribe<SteamUser.LoggedOnCallback>(x => _loggedOn.TrySetResult(x));
+            _manager.Subscribe<SteamClient.DisconnectedCallback>(_ =>
+            {
+                var e = new MbssException(ErrorKind.Download, "Lost the connection to Steam!");
+                _connected.TrySetException(e);
+                _loggedOn.TrySetException(e);
+            });
+
+            _callbacks = Task.Run(() =>
+            {
+                while (!_stop.IsCancellationRequested) _manager.RunWaitCallbacks(TimeSpan.FromMilliseconds(500));
+            });
+        }
+
+        // Without an account the session logs in anonymously
+        public static async Task<SteamSession> Open(SteamAccount? account)
+        {
+            var session = new SteamSession();
+            try
+            {
+                await session.LogOn(account);
+                return session;
+            }
+            catch
+            {
+                session.Dispose();
+                throw;
+            }
+        }
+
+        public async Task<byte[]> DepotKey(uint depotId)
+        {
+            var key = await _apps.GetDepotDecryptionKey(depotId, AppId).ToTask().WaitAsync(Timeout);
+            if (key.Result != EResult.OK)
+                throw new MbssException(ErrorKind.SteamAuth,
+                    $"Steam refused the key for depot {depotId} ({key.Result}), does the account own Beat Saber?");
+
+            return key.DepotKey;
+        }
+
+        public async Task<DepotManifest> Manifest(uint depotId, ulong manifestId, string? branch, byte[] depotKey)
+        {
+            var requestCode = await _content.GetManifestRequestCode(depotId, AppId, manifestId, branch ?? "public");
+            if (requestCode == 0)
+                throw new MbssException(ErrorKind.ManifestNotFound,
+                    $"Steam has no manifest {manifestId} for depot {depotId}!");
+
+            var manifest = await WithServer(server =>
+                _cdn.DownloadManifestAsync(depotId, manifestId, requestCode, server, depotKey));
+            if (manifest.FilenamesEncrypted) manifest.DecryptFilenames(depotKey);
+            return manifest;
+        }
+
+        public async Task<byte[]> Chunk(uint depotId, DepotManifest.ChunkData chunk, byte[] depotKey)
+        {
+            var downloaded = await WithServer(server => _cdn.DownloadDepotChunkAsync(depotId, chunk, server, depotKey));
+            return downloaded.Data;
+        }
+
+        public void Dispose()
+        {
+            if (_client.IsConnected) _client.Disconnect();
+            _stop.Cancel();
+            _callbacks.Wait();
+            _cdn.Dispose();
+            _stop.Dispose();
+        }
+
+        private async Task LogOn(SteamAccount? account)
+        {
+            _client.Connect();
+            await _connected.Task.WaitAsync(Timeout);
+
+            if (account == null)
+            {
+                _user.LogOnAnonymous();
+            }
+            else
+            {
+                try
+                {
+                    var auth = await _client.Authentication.BeginAuthSessionViaCredentialsAsync(new AuthSessionDetails
+                    {
+                        Username = account.Username,
+                        Password = account.Password,
+                        Authenticator = new GuardAuthenticator(account)
+                    });
+                    var result = await auth.PollingWaitForResultAsync();
+                    _user.LogOn(new SteamUser.LogOnDetails
+                    {
+                        Username = result.AccountName,
+                        AccessToken = result.RefreshToken
+                    });
+                }
+                catch (AuthenticationException e)
+                {
+                    throw LogOnFailed(account, e.Result);
+                }
+            }
+
+            var loggedOn = await _loggedOn.Task.WaitAsync(Timeout);
+            if (loggedOn.Result != EResult.OK) throw LogOnFailed(account, loggedOn.Result);
+
+            // Only Steam's own CDN and caches serve depot chunks, the other server types are for other content
+            _servers = (await _content.GetServersForSteamPipe())
+                .Where(x => x.Type is "SteamCache" or "CDN")
+                .OrderBy(x => x.WeightedLoad)
+                .ToList();
+            if (_servers.Count == 0)
+                throw new MbssException(ErrorKind.Download, "Steam didn't list any content servers!");
+        }
+
+        // Chunks are spread over the servers, a failing one is simply replaced by another on the next attempt
+        private async Task<T> WithServer<T>(Func<Server, Task<T>> request)
+        {
+            for (var attempt = 1;; attempt++)
+            {
+                var server = _servers[Random.Shared.Next(Math.Min(_servers.Count, 5))];
+                try
+                {
+                    return await request(server);
+                }
+                catch (Exception e) when (attempt < MaxAttempts && e is HttpRequestException
+                                              or SteamKitWebRequestException or TaskCanceledException
+                                              or InvalidDataException)
+                {
+                    await Task.Delay(TimeSpan.FromSeconds(attempt));
+                }
+            }
+        }
+
+        private static MbssException LogOnFailed(SteamAccount? account, EResult result)
+        {
+            var name = account?.Username ?? "anonymous";
+            return result is EResult.RateLimitExceeded or EResult.AccountLoginDeniedThrottle
+                ? new MbssException(ErrorKind.RateLimited, $"Steam is rate limiting logins of {name}.")
+                : new MbssException(ErrorKind.SteamAuth, $"Steam rejected the login of {name} ({result})!");
+        }
+    }
+
+    // Codes come from the same place DepotDownloader's prompts are answered from
+    private class GuardAuthenticator : IAuthenticator
+    {
+        private readonly SteamAccount _account;
+        private int _attempts;
+
+        public GuardAuthenticator(SteamAccount account)
+        {
+            _account = account;
+        }
+
+        public Task<string> GetDeviceCodeAsync(bool previousCodeWasIncorrect)
+        {
+            return Task.FromResult(Code());
+        }
+
+        public Task<string> GetEmailCodeAsync(string email, bool previousCodeWasIncorrect)
+        {
+            return Task.FromResult(Code());
+        }
+
+        // Nobody is around to confirm the login in the Steam app, so Steam falls back to asking for a code
+        public Task<bool> AcceptDeviceConfirmationAsync()
+        {
+            return Task.FromResult(false);
+        }
+
+        private string Code()
+        {
+            return SteamGuard.GetCode(_account, _attempts++) ??
+                   throw new MbssException(ErrorKind.SteamAuth,
+                       "Steam Guard 2FA code required! Set MBSS_STEAM_TOTP_SECRET or MBSS_STEAM_GUARD_CODE.");
+        }
+    }
+}
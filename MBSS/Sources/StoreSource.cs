@@ -0,0 +1,31 @@
+﻿namespace MBSS.Sources;
+
+// Routes every version to the store it was released on, Steam unless versions.json says otherwise
+internal class StoreSource : IDepotSource
+{
+    private readonly IDepotSource _steam;
+    private readonly IDepotSource _oculus;
+
+    public StoreSource(IDepotSource steam, IDepotSource oculus)
+    {
+        _steam = steam;
+        _oculus = oculus;
+    }
+
+    public bool RequiresSteam => _steam.RequiresSteam;
+    public bool RequiresDepotDownloader => _steam.RequiresDepotDownloader;
+
+    public async Task Preflight()
+    {
+        await _steam.Preflight();
+
+        // Only mirrors that actually list Oculus builds need a token
+        var versions = File.Exists("versions.json") ? await BeatSaberVersion.LoadAll() : null;
+        if (versions?.Any(x => x.IsOculus) == true) await _oculus.Preflight();
+    }
+
+    public Task<string> Fetch(BeatSaberVersion version, string downloadPath)
+    {
+        return (version.IsOculus ? _oculus : _steam).Fetch(version, downloadPath);
+    }
+}
@@ -0,0 +1,110 @@
+﻿using Newtonsoft.Json;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal class SteamAccount
+{
+    [JsonProperty("username")] public string Username { get; set; } = string.Empty;
+    [JsonProperty("password")] public string Password { get; set; } = string.Empty;
+    [JsonProperty("totp_secret")] public string? TotpSecret { get; set; }
+    [JsonProperty("guard_code")] public string? GuardCode { get; set; }
+
+    // Each account gets its own DepotDownloader session, they'd overwrite each other's remembered login
+    [JsonIgnore] public string SessionDirectory => Path.Combine(SteamAccounts.SessionRoot, Username);
+    [JsonIgnore] public DateTimeOffset CooldownUntil { get; set; }
+}
+
+internal static class SteamAccounts
+{
+    private static List<SteamAccount>? _all;
+    private static int _next;
+
+    public static string? PoolFile => Environment.GetEnvironmentVariable("MBSS_STEAM_ACCOUNTS_FILE");
+    public static bool IsPool => !string.IsNullOrEmpty(PoolFile);
+
+    // The pool file replaces STEAM_USERNAME and STEAM_PASSWORD, so those are only required without one
+    public static string[] CredentialEnvs =>
+        IsPool || AnonymousOnly ? Array.Empty<string>() : new[] { "STEAM_USERNAME", "STEAM_PASSWORD" };
+
+    // Mirrors of freely available depots or Oculus builds never log in, so they don't need an account at all
+    public static bool AnonymousOnly
+    {
+        get
+        {
+            if (!File.Exists("versions.json")) return false;
+
+            var versions = JsonConvert.DeserializeObject<List<BeatSaberVersion>>(File.ReadAllText("versions.json"));
+            return versions is { Count: > 0 } && versions.All(x => x.IsAnonymous || x.IsOculus);
+        }
+    }
+
+    public static List<SteamAccount> All => _all ??= Load();
+
+    private static TimeSpan Cooldown =>
+        Durations.FromEnvironment("MBSS_STEAM_ACCOUNT_COOLDOWN", TimeSpan.FromMinutes(30)) ?? TimeSpan.Zero;
+
+    // Kept apart from DepotDownloader's own default so mirrors sharing a host don't share sessions by accident
+    public static string SessionRoot =>
+        Environment.GetEnvironmentVariable("MBSS_STEAM_SESSION_DIR") ?? DefaultSessionRoot;
+
+    private static string DefaultSessionRoot =>
+        Path.Combine(Environment.GetFolderPath(Environment.SpecialFolder.LocalApplicationData), "mbss", "sessions");
+
+    // Round robin spreads a backfill over all accounts, ones cooling down after being throttled are skipped
+    public static SteamAccount Acquire()
+    {
+        var now = DateTimeOffset.UtcNow;
+        for (var i = 0; i < All.Count; i++)
+        {
+            var account = All[(_next + i) % All.Count];
+            if (account.CooldownUntil > now) continue;
+
+            _next = (_next + i + 1) % All.Count;
+            return account;
+        }
+
+        return All.MinBy(x => x.CooldownUntil)!;
+    }
+
+    // Returns whether another account can take over right away
+    public static bool Throttled(SteamAccount account)
+    {
+        account.CooldownUntil = DateTimeOffset.UtcNow + Cooldown;
+        AnsiConsole.MarkupLine($"[yellow]Steam throttled {account.Username.EscapeMarkup()}, " +
+                               $"cooling it down until {account.CooldownUntil.ToLocalTime():t}[/]");
+        return All.Any(x => x.CooldownUntil <= DateTimeOffset.UtcNow);
+    }
+
+    private static List<SteamAccount> Load()
+    {
+        if (!IsPool)
+        {
+            return new List<SteamAccount>
+            {
+                new()
+                {
+                    Username = Environment.GetEnvironmentVariable("STEAM_USERNAME") ?? string.Empty,
+                    Password = Environment.GetEnvironmentVariable("STEAM_PASSWORD") ?? string.Empty,
+                    TotpSecret = Environment.GetEnvironmentVariable("MBSS_STEAM_TOTP_SECRET"),
+                    GuardCode = Environment.GetEnvironmentVariable("MBSS_STEAM_GUARD_CODE")
+                }
+            };
+        }
+
+        if (!File.Exists(PoolFile))
+            throw new MbssException(ErrorKind.Config, $"MBSS_STEAM_ACCOUNTS_FILE {PoolFile} does not exist!");
+
+        var accounts = JsonConvert.DeserializeObject<List<SteamAccount>>(File.ReadAllText(PoolFile!));
+        if (accounts == null || accounts.Count == 0 || accounts.Any(x => string.IsNullOrEmpty(x.Username)))
+            throw new MbssException(ErrorKind.Config, $"{PoolFile} must list accounts with a username and password!");
+
+        foreach (var account in accounts)
+        {
+            Secrets.Add(account.Password);
+            if (account.TotpSecret != null) Secrets.Add(account.TotpSecret);
+        }
+
+        return accounts;
+    }
+}
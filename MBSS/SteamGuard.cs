@@ -0,0 +1,50 @@
+﻿using System.Buffers.Binary;
+using System.Security.Cryptography;
+using System.Text;
+
+namespace MBSS;
+
+internal static class SteamGuard
+{
+    private const string CodeAlphabet = "23456789BCDFGHJKMNPQRTVWXY";
+    private const int MaxTotpAttempts = 3;
+
+    public static bool IsPrompt(string line)
+    {
+        return line.Contains("2-factor auth code", StringComparison.OrdinalIgnoreCase) ||
+               line.Contains("2 factor auth code", StringComparison.OrdinalIgnoreCase) ||
+               line.Contains("auth code sent to the email", StringComparison.OrdinalIgnoreCase) ||
+               line.Contains("authentication code sent", StringComparison.OrdinalIgnoreCase);
+    }
+
+    public static string? GetCode(SteamAccount account, int attempt)
+    {
+        var secret = account.TotpSecret;
+        if (!string.IsNullOrEmpty(secret))
+            return attempt < MaxTotpAttempts ? GenerateTotp(secret, DateTimeOffset.UtcNow) : null;
+
+        var code = account.GuardCode;
+        return !string.IsNullOrEmpty(code) && attempt == 0 ? code : null;
+    }
+
+    private static string GenerateTotp(string sharedSecret, DateTimeOffset time)
+    {
+        var timeBytes = new byte[8];
+        BinaryPrimitives.WriteInt64BigEndian(timeBytes, time.ToUnixTimeSeconds() / 30);
+
+        using var hmac = new HMACSHA1(Convert.FromBase64String(sharedSecret));
+        var hash = hmac.ComputeHash(timeBytes);
+
+        var offset = hash[19] & 0xF;
+        var fullCode = BinaryPrimitives.ReadInt32BigEndian(hash.AsSpan(offset, 4)) & 0x7FFFFFFF;
+
+        var code = new StringBuilder();
+        for (var i = 0; i < 5; i++)
+        {
+            code.Append(CodeAlphabet[fullCode % CodeAlphabet.Length]);
+            fullCode /= CodeAlphabet.Length;
+        }
+
+        return code.ToString();
+    }
+}
@@ -0,0 +1,93 @@
+﻿using System.Diagnostics;
+using System.Text.RegularExpressions;
+using MBSS.Sources;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal static class SteamWatcher
+{
+    private const string AppId = "620980";
+    private const string DepotId = "620981";
+
+    private static readonly Regex SemverRegex = new(@"^\d+\.\d+\.\d+([-+][0-9A-Za-z.\-+]+)?$");
+
+    public static async Task<List<BeatSaberVersion>> Discover(List<BeatSaberVersion> known)
+    {
+        AnsiConsole.MarkupLine("[yellow]Polling Steam for new Beat Saber builds...[/]");
+
+        var steamCmd = new Process
+        {
+            StartInfo =
+            {
+                FileName = Environment.GetEnvironmentVariable("MBSS_STEAMCMD_PATH") ?? "steamcmd",
+                Arguments = $"+login anonymous +app_info_update 1 +app_info_print {AppId} +quit",
+                RedirectStandardOutput = true,
+                UseShellExecute = false
+            }
+        };
+
+        steamCmd.Start();
+        var output = await steamCmd.StandardOutput.ReadToEndAsync();
+        await steamCmd.WaitForExitAsync();
+        if (steamCmd.ExitCode != 0)
+            throw new MbssException(ErrorKind.Download, $"steamcmd exited with code {steamCmd.ExitCode}!");
+
+        var start = Regex.Match(output, $"\"{AppId}\"\\s*\\{{");
+        if (!start.Success) throw new MbssException(ErrorKind.Download, "Failed to find app info in steamcmd output!");
+
+        var appInfo = Vdf.ParseFirst(output[start.Index..]).GetObject(AppId);
+        var branches = appInfo?.GetObject("depots", "branches");
+        var manifests = appInfo?.GetObject("depots", DepotId, "manifests");
+        if (branches == null || manifests == null)
+            throw new MbssException(ErrorKind.Download, "Failed to parse app info from steamcmd!");
+
+        var discovered = new List<BeatSaberVersion>();
+        foreach (var (branch, _) in branches)
+        {
+            // Older builds are kept as beta branches named after their version
+            if (!SemverRegex.IsMatch(branch)) continue;
+
+            var manifest = manifests.GetObject(branch)?.GetString("gid") ?? manifests.GetString(branch);
+            if (manifest == null) continue;
+            if (known.Any(x => x.Version == branch || x.Manifest == manifest)) continue;
+
+            AnsiConsole.MarkupLine($"[green]Discovered version {branch} (manifest {manifest})[/]");
+            discovered.Add(new BeatSaberVersion { GameVersion = branch, Manifest = manifest });
+        }
+
+        // New releases only ever show up on public, named by nothing but the build itself
+        var publicManifest = manifests.GetObject("public")?.GetString("gid") ?? manifests.GetString("public");
+        var buildId = branches.GetObject("public")?.GetString("buildid");
+        if (publicManifest != null && known.Concat(discovered).All(x => x.Manifest != publicManifest))
+        {
+            var version = await ReadVersion(publicManifest);
+            if (known.Concat(discovered).Any(x => x.Version == version))
+            {
+                AnsiConsole.MarkupLine(
+                    $"[yellow]Public build {buildId} (manifest {publicManifest}) is {version}, which is already " +
+                    "listed with a different manifest.[/]");
+            }
+            else
+            {
+                AnsiConsole.MarkupLine(
+                    $"[green]Discovered version {version} (build {buildId}, manifest {publicManifest})[/]");
+                discovered.Add(new BeatSaberVersion { GameVersion = version, Manifest = publicManifest });
+            }
+        }
+
+        if (discovered.Count == 0) AnsiConsole.MarkupLine("[yellow]No new builds discovered.[/]");
+        return discovered;
+    }
+
+    private static async Task<string> ReadVersion(string manifest)
+    {
+        using var path = new TempPath();
+        var build = new BeatSaberVersion { Manifest = manifest };
+        await new DepotDownloaderSource().FetchVersionFiles(build, path.Path);
+
+        return await VersionCheck.ReadGameVersion(path.Path) ??
+               throw new MbssException(ErrorKind.Download,
+                   $"Failed to determine the game version of public manifest {manifest}!");
+    }
+}
@@ -0,0 +1,33 @@
+﻿using Spectre.Console;
+
+namespace MBSS;
+
+internal static class SupportFiles
+{
+    private static readonly Dictionary<string, string> Templates = new()
+    {
+        [".gitignore"] = "main.gitignore",
+        [".gitattributes"] = "main.gitattributes"
+    };
+
+    public static async Task<List<string>> Sync()
+    {
+        var changed = new List<string>();
+        foreach (var (file, asset) in Templates)
+        {
+            var path = Path.GetFullPath(file);
+            var lines = Assets.Read(asset).ReplaceLineEndings("\n").TrimEnd('\n').Split('\n');
+            var existing = File.Exists(path) ? await File.ReadAllLinesAsync(path) : Array.Empty<string>();
+
+            // Lines are only ever added, so anything the user changed or appended survives an update
+            var missing = lines.Where(x => !existing.Any(y => y.Trim() == x.Trim())).ToList();
+            if (missing.Count == 0) continue;
+
+            await File.WriteAllLinesAsync(path, existing.Concat(missing));
+            AnsiConsole.MarkupLine($"[green]Updated {file} with {missing.Count} new entries[/]");
+            changed.Add(path);
+        }
+
+        return changed;
+    }
+}
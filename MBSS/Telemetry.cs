@@ -0,0 +1,30 @@
+﻿using System.Diagnostics;
+using OpenTelemetry;
+using OpenTelemetry.Resources;
+using OpenTelemetry.Trace;
+
+namespace MBSS;
+
+internal static class Telemetry
+{
+    public static readonly ActivitySource Source = new("MBSS");
+
+    private static TracerProvider? _provider;
+
+    public static void Init()
+    {
+        // The exporter reads the rest of its configuration from the standard OTEL_* variables
+        if (string.IsNullOrEmpty(Environment.GetEnvironmentVariable("OTEL_EXPORTER_OTLP_ENDPOINT"))) return;
+
+        _provider = Sdk.CreateTracerProviderBuilder()
+            .SetResourceBuilder(ResourceBuilder.CreateDefault().AddService("mbss"))
+            .AddSource(Source.Name)
+            .AddOtlpExporter()
+            .Build();
+    }
+
+    public static void Shutdown()
+    {
+        _provider?.Dispose();
+    }
+}
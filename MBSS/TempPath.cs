@@ -0,0 +1,63 @@
+﻿using System.Diagnostics;
+
+namespace MBSS;
+
+// Scratch files live under one directory named after the owning process, so whatever a crash leaks can be swept later
+internal sealed class TempPath : IDisposable
+{
+    public TempPath(string extension = "")
+    {
+        Directory.CreateDirectory(Root);
+        Path = System.IO.Path.Combine(Root, $"{Environment.ProcessId}-{System.IO.Path.GetRandomFileName()}{extension}");
+    }
+
+    public static string Root => System.IO.Path.Combine(System.IO.Path.GetTempPath(), "mbss");
+
+    public string Path { get; }
+
+    public void Dispose()
+    {
+        try
+        {
+            if (Directory.Exists(Path)) Directory.Delete(Path, true);
+            else if (File.Exists(Path)) File.Delete(Path);
+        }
+        catch (IOException)
+        {
+            // Still in use by a child process, the next sweep gets it
+        }
+    }
+
+    public static void Sweep()
+    {
+        if (!Directory.Exists(Root)) return;
+
+        foreach (var entry in new DirectoryInfo(Root).EnumerateFileSystemInfos())
+        {
+            var separator = entry.Name.IndexOf('-');
+            if (separator == -1 || !int.TryParse(entry.Name[..separator], out var pid) || IsRunning(pid)) continue;
+
+            try
+            {
+                if (entry is DirectoryInfo directory) directory.Delete(true);
+                else entry.Delete();
+            }
+            catch (IOException)
+            {
+            }
+        }
+    }
+
+    private static bool IsRunning(int pid)
+    {
+        try
+        {
+            using var process = Process.GetProcessById(pid);
+            return !process.HasExited;
+        }
+        catch (ArgumentException)
+        {
+            return false;
+        }
+    }
+}
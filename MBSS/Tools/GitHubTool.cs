@@ -0,0 +1,199 @@
+﻿using System.Net;
+using System.Runtime.InteropServices;
+using Newtonsoft.Json;
+using Newtonsoft.Json.Linq;
+using Spectre.Console;
+
+namespace MBSS.Tools;
+
+internal class GitHubTool
+{
+    public static readonly GitHubTool DepotDownloader =
+        new("DepotDownloader", "SteamRE/DepotDownloader", name => name.Contains(PlatformTag));
+
+    public static readonly GitHubTool GenericStripper =
+        new("GenericStripper", "beat-forge/GenericStripper", name => name.Contains("GenericStripper"));
+
+    private readonly Func<string, bool> _assetMatcher;
+
+    public GitHubTool(string name, string repository, Func<string, bool> assetMatcher)
+    {
+        Name = name;
+        Repository = repository;
+        _assetMatcher = assetMatcher;
+    }
+
+    public string Name { get; }
+    public string Repository { get; }
+
+    public string? VersionPin => Environment.GetEnvironmentVariable($"MBSS_{Name.ToUpperInvariant()}_VERSION");
+    public string? PathOverride => Environment.GetEnvironmentVariable($"MBSS_{Name.ToUpperInvariant()}_PATH");
+
+    public static string ToolsDirectory =>
+        Environment.GetEnvironmentVariable("MBSS_TOOLS_DIR") ?? DefaultToolsDirectory;
+
+    // LocalApplicationData resolves to $XDG_DATA_HOME (or ~/.local/share) outside of Windows
+    private static string DefaultToolsDirectory =>
+        Path.Combine(Environment.GetFolderPath(Environment.SpecialFolder.LocalApplicationData), "mbss", "tools");
+
+    public string ExecutablePath =>
+        PathOverride ?? Path.Combine(ToolsDirectory, OperatingSystem.IsWindows() ? $"{Name}.exe" : Name);
+
+    public bool IsInstalled => File.Exists(ExecutablePath);
+
+    public string? InstalledVersion => File.Exists(VersionFile) ? File.ReadAllText(VersionFile).Trim() : null;
+
+    private string VersionFile => Path.Combine(ToolsDirectory, $"{Name}.version");
+
+    private static string PlatformTag
+    {
+        get
+        {
+            var os = OperatingSystem.IsWindows() ? "windows" : OperatingSystem.IsMacOS() ? "macos" : "linux";
+            var arch = RuntimeInformation.OSArchitecture switch
+            {
+                Architecture.Arm64 => "arm64",
+                Architecture.Arm => "arm",
+                _ => "x64"
+            };
+
+            return $"{os}-{arch}";
+        }
+    }
+
+    private static string CacheDirectory => Path.Combine(ToolsDirectory, ".cache");
+
+    public static void MigrateLegacyDirectory()
+    {
+        const string legacyDirectory = "bin";
+        if (!Directory.Exists(legacyDirectory) ||
+            Path.GetFullPath(legacyDirectory) == Path.GetFullPath(ToolsDirectory)) return;
+
+        AnsiConsole.MarkupLine($"[yellow]Moving tools from ./{legacyDirectory} to {ToolsDirectory}...[/]");
+        foreach (var file in Directory.EnumerateFiles(legacyDirectory, "*", SearchOption.AllDirectories).ToList())
+        {
+            var target = Path.Combine(ToolsDirectory, Path.GetRelativePath(legacyDirectory, file));
+            Directory.CreateDirectory(Path.GetDirectoryName(target)!);
+            if (!File.Exists(target)) File.Move(file, target);
+        }
+
+        Directory.Delete(legacyDirectory, true);
+    }
+
+    public async Task<string> GetTargetVersion(HttpClient client)
+    {
+        return (await GetRelease(client))["tag_name"]?.ToString() ?? "unknown";
+    }
+
+    public async Task Install(HttpClient client)
+    {
+        AnsiConsole.MarkupLine($"[yellow]Installing {Name} to {ToolsDirectory}...[/]");
+
+        Dictionary<string, dynamic> release;
+        try
+        {
+            release = await GetRelease(client);
+        }
+        catch (RateLimitException)
+        {
+            var cached = FindCachedArchive();
+            if (cached == null) throw;
+
+            AnsiConsole.MarkupLine($"[yellow]GitHub API is rate limited, using cached {Path.GetFileName(cached)}[/]");
+            Extract(cached);
+            await AtomicFile.WriteAllTextAsync(VersionFile,
+                Path.GetFileNameWithoutExtension(cached)[(Name.Length + 1)..]);
+            return;
+        }
+
+        var assets = release["assets"] as JArray;
+        var asset = assets?.FirstOrDefault(x => _assetMatcher(x["name"]?.ToString() ?? string.Empty));
+        if (asset == null)
+            throw new MbssException(ErrorKind.ToolDownload, $"Failed to find a {Name} asset for this system!");
+
+        var tag = release["tag_name"]?.ToString() ?? "latest";
+        await DownloadAndExtract(client, asset["browser_download_url"]?.ToString(), tag);
+        await AtomicFile.WriteAllTextAsync(VersionFile, tag);
+    }
+
+    private async Task<Dictionary<string, dynamic>> GetRelease(HttpClient client)
+    {
+        var release = VersionPin == null ? "latest" : $"tags/{VersionPin}";
+        var res = await GitHubApi.Get(client, $"https://api.github.com/repos/{Repository}/releases/{release}");
+        if (res.StatusCode != HttpStatusCode.OK)
+            throw new MbssException(ErrorKind.ToolDownload, $"Failed to get {Name} release ({(int)res.StatusCode})!");
+
+        var latestRelease =
+            JsonConvert.DeserializeObject<Dictionary<string, dynamic>>(await res.Content.ReadAsStringAsync());
+        return latestRelease ?? throw new MbssException(ErrorKind.ToolDownload, $"Failed to parse {Name} release!");
+    }
+
+    private string? FindCachedArchive()
+    {
+        if (!Directory.Exists(CacheDirectory)) return null;
+
+        var archives = Directory.GetFiles(CacheDirectory, $"{Name}-*.zip");
+        return VersionPin == null
+            ? archives.MaxBy(x => File.GetLastWriteTimeUtc(x))
+            : archives.FirstOrDefault(x => Path.GetFileName(x) == $"{Name}-{VersionPin}.zip");
+    }
+
+    private async Task DownloadAndExtract(HttpClient client, string? url, string tag)
+    {
+        using var assetRes = await client.GetAsync(url, HttpCompletionOption.ResponseHeadersRead);
+        if (assetRes.StatusCode != HttpStatusCode.OK)
+            throw new MbssException(ErrorKind.ToolDownload, $"Failed to download {Name} asset!");
+
+        // Stream to disk rather than buffering the whole archive in memory
+        using var zip = new TempPath(".zip");
+        await AnsiConsole.Progress().StartAsync(async ctx =>
+        {
+            var length = assetRes.Content.Headers.ContentLength;
+            var task = ctx.AddTask($"Downloading {Name}", maxValue: length ?? 1);
+            task.IsIndeterminate = length == null;
+
+            await using var assetStream = await assetRes.Content.ReadAsStreamAsync();
+            await using var zipFile = File.Create(zip.Path);
+
+            var buffer = new byte[81920];
+            int read;
+            while ((read = await assetStream.ReadAsync(buffer)) > 0)
+            {
+                await zipFile.WriteAsync(buffer.AsMemory(0, read));
+                task.Increment(read);
+            }
+
+            task.Value = task.MaxValue;
+        });
+
+        Extract(zip.Path);
+
+        // Kept around so a rate limited API doesn't block reinstalling the tool
+        Directory.CreateDirectory(CacheDirectory);
+        var cached = Path.Combine(CacheDirectory, $"{Name}-{tag}.zip");
+        FileCopy.Copy(zip.Path, $"{cached}.tmp");
+        File.Move($"{cached}.tmp", cached, true);
+    }
+
+    // A crash mid-extract must not leave a half written executable behind that IsInstalled would accept
+    private void Extract(string zipPath)
+    {
+        var staging = Path.Combine(CacheDirectory, "staging");
+        if (Directory.Exists(staging)) Directory.Delete(staging, true);
+
+        try
+        {
+            SafeZip.Extract(zipPath, staging, ErrorKind.ToolDownload);
+            foreach (var file in Directory.EnumerateFiles(staging, "*", SearchOption.AllDirectories).ToList())
+            {
+                var target = Path.Combine(ToolsDirectory, Path.GetRelativePath(staging, file));
+                Directory.CreateDirectory(Path.GetDirectoryName(target)!);
+                File.Move(file, target, true);
+            }
+        }
+        finally
+        {
+            if (Directory.Exists(staging)) Directory.Delete(staging, true);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+﻿using System.IO.Compression;
+
+namespace MBSS.Tools;
+
+// Tool archives come from third party releases, so nothing in them is trusted to stay inside the tools directory
+internal static class SafeZip
+{
+    private const int MaxEntries = 10000;
+    private const long MaxExtractedBytes = 2L * 1024 * 1024 * 1024;
+    private const int UnixTypeMask = 0xF000;
+    private const int UnixSymlink = 0xA000;
+
+    // Failures are reported as the kind the caller passes in, a broken depot archive isn't a tool download problem
+    public static void Extract(string zipPath, string destination, ErrorKind kind, long maxBytes = MaxExtractedBytes)
+    {
+        var root = Path.GetFullPath(destination).TrimEnd(Path.DirectorySeparatorChar) + Path.DirectorySeparatorChar;
+        using var archive = ZipFile.OpenRead(zipPath);
+        if (archive.Entries.Count > MaxEntries)
+            throw new MbssException(kind,
+                $"{Path.GetFileName(zipPath)} has more than {MaxEntries} entries!");
+
+        long extracted = 0;
+        foreach (var entry in archive.Entries)
+        {
+            var path = Path.GetFullPath(Path.Combine(root, entry.FullName));
+            if (!path.StartsWith(root, StringComparison.Ordinal))
+                throw new MbssException(kind,
+                    $"{Path.GetFileName(zipPath)} contains an entry outside the target: {entry.FullName}!");
+
+            var unixMode = (entry.ExternalAttributes >> 16) & 0xFFFF;
+            if ((unixMode & UnixTypeMask) == UnixSymlink)
+                throw new MbssException(kind,
+                    $"{Path.GetFileName(zipPath)} contains a symlink: {entry.FullName}!");
+
+            if (entry.FullName.EndsWith('/') || entry.FullName.EndsWith('\\'))
+            {
+                Directory.CreateDirectory(path);
+                continue;
+            }
+
+            Directory.CreateDirectory(Path.GetDirectoryName(path)!);
+            using (var input = entry.Open())
+            using (var output = File.Create(path))
+            {
+                // The sizes in the central directory can lie, so count what actually comes out
+                var buffer = new byte[81920];
+                int read;
+                while ((read = input.Read(buffer)) > 0)
+                {
+                    extracted += read;
+                    if (extracted > maxBytes)
+                        throw new MbssException(kind,
+                            $"{Path.GetFileName(zipPath)} extracts to more than {Sizes.Format(maxBytes)}!");
+
+                    output.Write(buffer, 0, read);
+                }
+            }
+
+            if (!OperatingSystem.IsWindows() && (unixMode & 0x1FF) != 0)
+                File.SetUnixFileMode(path, (UnixFileMode)(unixMode & 0x1FF));
+        }
+    }
+}
@@ -0,0 +1,63 @@
+﻿using Spectre.Console;
+
+namespace MBSS.Tools;
+
+internal static class ToolsCommand
+{
+    private static readonly GitHubTool[] Tools = { GitHubTool.DepotDownloader, GitHubTool.GenericStripper };
+
+    public static async Task Run(HttpClient client, string[] args)
+    {
+        GitHubTool.MigrateLegacyDirectory();
+
+        switch (args.FirstOrDefault())
+        {
+            case "status":
+                await Status(client);
+                break;
+            case "upgrade":
+                await Upgrade(client);
+                break;
+            default:
+                AnsiConsole.MarkupLine("[red]Usage: MBSS tools <status|upgrade>[/]");
+                break;
+        }
+    }
+
+    private static async Task Status(HttpClient client)
+    {
+        var table = new Table().AddColumns("Tool", "Path", "Installed", "Target");
+        foreach (var tool in Tools)
+        {
+            var installed = !tool.IsInstalled ? "[red]missing[/]" : tool.InstalledVersion ?? "unknown";
+            var target = tool.PathOverride != null ? "overridden" : await tool.GetTargetVersion(client);
+            if (tool.VersionPin != null) target += " (pinned)";
+
+            table.AddRow(tool.Name, tool.ExecutablePath.EscapeMarkup(), installed, target);
+        }
+
+        AnsiConsole.Write(table);
+    }
+
+    private static async Task Upgrade(HttpClient client)
+    {
+        foreach (var tool in Tools)
+        {
+            if (tool.PathOverride != null)
+            {
+                AnsiConsole.MarkupLine($"[yellow]{tool.Name} path is overridden, skipping...[/]");
+                continue;
+            }
+
+            var target = await tool.GetTargetVersion(client);
+            if (tool.IsInstalled && tool.InstalledVersion == target)
+            {
+                AnsiConsole.MarkupLine($"[green]{tool.Name} is up to date ({target})[/]");
+                continue;
+            }
+
+            await tool.Install(client);
+            AnsiConsole.MarkupLine($"[green]{tool.Name} upgraded to {target}![/]");
+        }
+    }
+}
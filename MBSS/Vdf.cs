@@ -0,0 +1,112 @@
+﻿using System.Text;
+
+namespace MBSS;
+
+internal static class Vdf
+{
+    public static Dictionary<string, object> Parse(string text)
+    {
+        var pos = 0;
+        return ParseObject(text, ref pos, false);
+    }
+
+    // Just the first key and its object, whatever comes after it (like the rest of steamcmd's output) is left alone
+    public static Dictionary<string, object> ParseFirst(string text)
+    {
+        var pos = 0;
+        var key = NextToken(text, ref pos) ?? throw new Exception("Unexpected end of VDF input!");
+        if (NextToken(text, ref pos) != "{") throw new Exception($"Expected an object for VDF key {key}!");
+
+        return new Dictionary<string, object>(StringComparer.OrdinalIgnoreCase)
+        {
+            [key] = ParseObject(text, ref pos, true)
+        };
+    }
+
+    public static Dictionary<string, object>? GetObject(this Dictionary<string, object> obj, params string[] path)
+    {
+        var current = obj;
+        foreach (var key in path)
+        {
+            if (!current.TryGetValue(key, out var next) || next is not Dictionary<string, object> nextObj) return null;
+            current = nextObj;
+        }
+
+        return current;
+    }
+
+    public static string? GetString(this Dictionary<string, object> obj, string key)
+    {
+        return obj.TryGetValue(key, out var value) ? value as string : null;
+    }
+
+    private static Dictionary<string, object> ParseObject(string text, ref int pos, bool nested)
+    {
+        var obj = new Dictionary<string, object>(StringComparer.OrdinalIgnoreCase);
+        while (true)
+        {
+            var key = NextToken(text, ref pos);
+            if (key == null)
+            {
+                if (nested) throw new Exception("Unexpected end of VDF input!");
+                return obj;
+            }
+
+            if (key == "}")
+            {
+                if (!nested) throw new Exception("Unexpected '}' in VDF input!");
+                return obj;
+            }
+
+            var value = NextToken(text, ref pos) ?? throw new Exception($"Missing value for VDF key {key}!");
+            obj[key] = value == "{" ? ParseObject(text, ref pos, true) : value;
+        }
+    }
+
+    private static string? NextToken(string text, ref int pos)
+    {
+        while (pos < text.Length)
+        {
+            if (char.IsWhiteSpace(text[pos]))
+            {
+                pos++;
+            }
+            else if (text[pos] == '/' && pos + 1 < text.Length && text[pos + 1] == '/')
+            {
+                while (pos < text.Length && text[pos] != '\n') pos++;
+            }
+            else
+            {
+                break;
+            }
+        }
+
+        if (pos >= text.Length) return null;
+
+        var c = text[pos];
+        if (c is '{' or '}')
+        {
+            pos++;
+            return c.ToString();
+        }
+
+        var sb = new StringBuilder();
+        if (c == '"')
+        {
+            pos++;
+            while (pos < text.Length && text[pos] != '"')
+            {
+                if (text[pos] == '\\' && pos + 1 < text.Length) pos++;
+                sb.Append(text[pos++]);
+            }
+
+            pos++;
+            return sb.ToString();
+        }
+
+        while (pos < text.Length && !char.IsWhiteSpace(text[pos]) && text[pos] is not ('{' or '}' or '"'))
+            sb.Append(text[pos++]);
+
+        return sb.ToString();
+    }
+}
@@ -0,0 +1,57 @@
+﻿using System.Text;
+using System.Text.RegularExpressions;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal static class VersionCheck
+{
+    private static readonly Regex VersionRegex = new(@"\d+\.\d+\.\d+");
+
+    public static async Task Run(BeatSaberVersion version, string depotPath)
+    {
+        var mode = Environment.GetEnvironmentVariable("MBSS_VERSION_CHECK")?.ToLowerInvariant() ?? "warn";
+        if (mode == "off") return;
+
+        var gameVersion = await ReadGameVersion(depotPath);
+        if (gameVersion == null)
+        {
+            AnsiConsole.MarkupLine(
+                $"[yellow]Could not determine the game version of {version.Version}, skipping check.[/]");
+            return;
+        }
+
+        // Build metadata and prerelease suffixes aren't part of the game's own version string
+        var declared = version.GameVersion.Split('+', '-')[0];
+        if (gameVersion == declared) return;
+
+        var message = $"versions.json declares {version.Version} but manifest {version.Manifest} is {gameVersion}!";
+        if (mode == "fail") throw new MbssException(ErrorKind.Config, message);
+        AnsiConsole.MarkupLine($"[yellow]{message.EscapeMarkup()}[/]");
+    }
+
+    public static async Task<string?> ReadGameVersion(string depotPath)
+    {
+        var versionTxt = Directory.EnumerateFiles(depotPath, "BeatSaberVersion.txt", SearchOption.AllDirectories)
+            .FirstOrDefault();
+        if (versionTxt != null)
+        {
+            var match = VersionRegex.Match(await File.ReadAllTextAsync(versionTxt));
+            if (match.Success) return match.Value;
+        }
+
+        var globalGameManagers = Path.Combine(depotPath, "Beat Saber_Data", "globalgamemanagers");
+        if (!File.Exists(globalGameManagers)) return null;
+
+        // The bundle version is the first version-like string after the app category in PlayerSettings
+        var bytes = await File.ReadAllBytesAsync(globalGameManagers);
+        var marker = Encoding.ASCII.GetBytes("public.app-category.games");
+        var index = bytes.AsSpan().IndexOf(marker);
+        if (index == -1) return null;
+
+        var start = index + marker.Length;
+        var window = Encoding.ASCII.GetString(bytes, start, Math.Min(256, bytes.Length - start));
+        var version = VersionRegex.Match(window);
+        return version.Success ? version.Value : null;
+    }
+}
@@ -0,0 +1,63 @@
+﻿using LibGit2Sharp;
+using Newtonsoft.Json;
+
+namespace MBSS;
+
+internal class DiffEntry
+{
+    [JsonProperty("path")] public string Path { get; set; } = string.Empty;
+    [JsonProperty("old_size")] public long? OldSize { get; set; }
+    [JsonProperty("new_size")] public long? NewSize { get; set; }
+    [JsonProperty("old_assembly_version")] public string? OldAssemblyVersion { get; set; }
+    [JsonProperty("new_assembly_version")] public string? NewAssemblyVersion { get; set; }
+}
+
+internal class VersionDiff
+{
+    [JsonProperty("from")] public string From { get; set; } = string.Empty;
+    [JsonProperty("to")] public string To { get; set; } = string.Empty;
+    [JsonProperty("added")] public List<DiffEntry> Added { get; set; } = new();
+    [JsonProperty("removed")] public List<DiffEntry> Removed { get; set; } = new();
+    [JsonProperty("modified")] public List<DiffEntry> Modified { get; set; } = new();
+    [JsonProperty("size_delta")] public long SizeDelta { get; set; }
+
+    public static VersionDiff Compute(Repository repo, string from, Tree fromTree, string to, Tree toTree)
+    {
+        var diff = new VersionDiff { From = from, To = to };
+        foreach (var change in repo.Diff.Compare<TreeChanges>(fromTree, toTree))
+        {
+            var oldBlob = change.Status == ChangeKind.Added ? null : repo.Lookup<Blob>(change.OldOid);
+            var newBlob = change.Status == ChangeKind.Deleted ? null : repo.Lookup<Blob>(change.Oid);
+
+            var entry = new DiffEntry
+            {
+                Path = change.Path.Replace('\\', '/'),
+                OldSize = oldBlob?.Size,
+                NewSize = newBlob?.Size
+            };
+
+            if (entry.Path.EndsWith(".dll", StringComparison.OrdinalIgnoreCase))
+            {
+                entry.OldAssemblyVersion = oldBlob == null ? null : AssemblyInfo.GetVersion(oldBlob.GetContentStream());
+                entry.NewAssemblyVersion = newBlob == null ? null : AssemblyInfo.GetVersion(newBlob.GetContentStream());
+            }
+
+            switch (change.Status)
+            {
+                case ChangeKind.Added:
+                    diff.Added.Add(entry);
+                    break;
+                case ChangeKind.Deleted:
+                    diff.Removed.Add(entry);
+                    break;
+                default:
+                    diff.Modified.Add(entry);
+                    break;
+            }
+
+            diff.SizeDelta += (entry.NewSize ?? 0) - (entry.OldSize ?? 0);
+        }
+
+        return diff;
+    }
+}
@@ -0,0 +1,46 @@
+﻿using LibGit2Sharp;
+
+namespace MBSS;
+
+internal static class VersionFiles
+{
+    private static readonly Dictionary<string, string> Templates = new()
+    {
+        [".gitattributes"] = "version.gitattributes",
+        [".gitignore"] = "version.gitignore",
+        ["README.md"] = "version.README.md"
+    };
+
+    public static async Task Write(BeatSaberVersion version, string versionPath, string stripperMode,
+        string? stripperArgs)
+    {
+        var remote = PublicRemote();
+        var values = new Dictionary<string, string>
+        {
+            ["version"] = version.Version,
+            ["manifest"] = version.Manifest,
+            ["platform"] = version.PlatformName,
+            ["date"] = DateTimeOffset.UtcNow.ToString("yyyy-MM-dd"),
+            ["stripper_mode"] = stripperMode,
+            ["stripper_args"] = string.IsNullOrEmpty(stripperArgs) ? "none" : stripperArgs,
+            ["remote"] = remote ?? "<repository url>"
+        };
+
+        foreach (var (file, asset) in Templates)
+            await File.WriteAllTextAsync(Path.Combine(versionPath, file), Assets.Render(asset, values));
+
+        // Branch names have to escape some version strings, this is where the exact one is kept
+        await File.WriteAllTextAsync(Path.Combine(versionPath, "version.txt"), version.Version + "\n");
+    }
+
+    // The origin URL goes into published files, a token in its userinfo would be published along with it
+    public static string? PublicRemote()
+    {
+        string? url;
+        using (var repo = new Repository(Workspace.RepositoryPath))
+            url = repo.Network.Remotes["origin"]?.Url;
+
+        if (url == null || !Uri.TryCreate(url, UriKind.Absolute, out var uri) || uri.UserInfo.Length == 0) return url;
+        return new UriBuilder(uri) { UserName = string.Empty, Password = string.Empty }.Uri.ToString();
+    }
+}
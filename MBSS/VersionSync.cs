@@ -0,0 +1,41 @@
+﻿using System.Net;
+using Newtonsoft.Json;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal static class VersionSync
+{
+    public static async Task<bool> Merge(HttpClient client, string url, List<BeatSaberVersion> versions)
+    {
+        AnsiConsole.MarkupLine($"[yellow]Fetching versions from {url}...[/]");
+
+        var res = await client.GetAsync(url);
+        if (res.StatusCode != HttpStatusCode.OK)
+            throw new MbssException(ErrorKind.Download, "Failed to fetch remote versions.json!");
+
+        var remoteVersions =
+            JsonConvert.DeserializeObject<List<BeatSaberVersion>>(await res.Content.ReadAsStringAsync());
+        if (remoteVersions == null) throw new MbssException(ErrorKind.Config, "Failed to parse remote versions.json!");
+
+        var changed = false;
+        foreach (var remoteVersion in remoteVersions)
+        {
+            var index = versions.FindIndex(x => x.Version == remoteVersion.Version);
+            if (index == -1)
+            {
+                AnsiConsole.MarkupLine($"[green]Added version {remoteVersion.Version} from remote source[/]");
+                versions.Add(remoteVersion);
+                changed = true;
+            }
+            else if (JsonConvert.SerializeObject(versions[index]) != JsonConvert.SerializeObject(remoteVersion))
+            {
+                AnsiConsole.MarkupLine($"[green]Updated version {remoteVersion.Version} from remote source[/]");
+                versions[index] = remoteVersion;
+                changed = true;
+            }
+        }
+
+        return changed;
+    }
+}
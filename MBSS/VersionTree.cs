@@ -0,0 +1,98 @@
+﻿using LibGit2Sharp;
+using Newtonsoft.Json;
+
+namespace MBSS;
+
+internal record VersionRefs(
+    [property: JsonProperty("tree")] string Tree,
+    [property: JsonProperty("commit")] string? Commit,
+    [property: JsonProperty("branch")] string? Branch);
+
+internal static class VersionTree
+{
+    public static Commit? MainTip(Repository repo)
+    {
+        return repo.Branches["main"]?.Tip ?? repo.Head.Tip;
+    }
+
+    public static Tree? Find(Repository repo, string version)
+    {
+        return MainTip(repo)?[$"versions/{version}"]?.Target as Tree;
+    }
+
+    // The closest earlier version of the same platform and store that was actually mirrored, which is what
+    // consumers upgrade from
+    public static (string Version, Tree Tree)? FindPrevious(Repository repo, List<BeatSaberVersion> versions,
+        string version)
+    {
+        var (gameVersion, variant) = BeatSaberVersion.SplitKey(version);
+        var current = SemVer.Parse(gameVersion);
+        var earlier = versions
+            .Where(x => x.Variant == variant)
+            .Select(x => (x.Version, SemVer: SemVer.Parse(x.GameVersion)))
+            .Where(x => x.SemVer.CompareTo(current) < 0)
+            .OrderByDescending(x => x.SemVer);
+        foreach (var candidate in earlier)
+            if (Find(repo, candidate.Version) is { } tree)
+                return (candidate.Version, tree);
+
+        return null;
+    }
+
+    // Maps every version on main to the newest commit that changed it, plus its orphan branch if there is one
+    public static Dictionary<string, VersionRefs> CommitIndex(Repository repo)
+    {
+        var index = new Dictionary<string, VersionRefs>();
+        var tip = MainTip(repo);
+        if (tip?["versions"]?.Target is not Tree versions) return index;
+
+        var pending = versions.Where(x => x.Target is Tree).ToDictionary(x => x.Name, x => x.Target.Id);
+        var commits = new Dictionary<string, string>();
+        var filter = new CommitFilter { IncludeReachableFrom = tip, SortBy = CommitSortStrategies.Topological };
+        foreach (var commit in repo.Commits.QueryBy(filter))
+        {
+            if (pending.Count == 0) break;
+
+            // The newest commit whose parent doesn't have the same tree for a version is the one that made it
+            foreach (var (version, id) in pending.ToList())
+            {
+                var parentId = commit.Parents.FirstOrDefault()?[$"versions/{version}"]?.Target.Id;
+                if (parentId == id) continue;
+
+                commits[version] = commit.Sha;
+                pending.Remove(version);
+            }
+        }
+
+        foreach (var version in versions.Where(x => x.Target is Tree))
+            index[version.Name] = new VersionRefs(version.Target.Sha, commits.GetValueOrDefault(version.Name),
+                repo.Branches[OrphanBranches.BranchName(version.Name)]?.Tip.Sha);
+
+        return index;
+    }
+
+    public static string WebUrl(string remoteUrl, string version)
+    {
+        var match = Preflight.GitHubRemoteRegex.Match(remoteUrl);
+        return match.Success
+            ? $"https://github.com/{match.Groups["owner"].Value}/{match.Groups["repo"].Value}/tree/main/versions/" +
+              Uri.EscapeDataString(version)
+            : remoteUrl;
+    }
+
+    public static IEnumerable<(string Path, TreeEntry Entry)> Walk(Tree tree, string prefix = "")
+    {
+        foreach (var entry in tree)
+        {
+            var path = prefix.Length == 0 ? entry.Name : $"{prefix}/{entry.Name}";
+            if (entry.Target is Tree subtree)
+            {
+                foreach (var child in Walk(subtree, path)) yield return child;
+            }
+            else if (entry.TargetType == TreeEntryTargetType.Blob)
+            {
+                yield return (path, entry);
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+﻿using System.Diagnostics;
+using Spectre.Console;
+
+namespace MBSS;
+
+// Kills a child process that runs too long or goes quiet, Steam connection problems can hang DepotDownloader forever
+internal sealed class Watchdog : IDisposable
+{
+    private const int TailLines = 40;
+
+    private readonly Process _process;
+    private readonly string _name;
+    private readonly TimeSpan? _idleTimeout;
+    private readonly CancellationTokenSource _timeout = new();
+    private readonly CancellationTokenSource _idle = new();
+    private readonly Queue<string> _tail = new();
+    private string? _tripped;
+
+    public Watchdog(Process process, string name, TimeSpan? timeout, TimeSpan? idleTimeout)
+    {
+        _process = process;
+        _name = name;
+        _idleTimeout = idleTimeout;
+
+        _timeout.Token.Register(() => Trip($"timed out after {timeout}"));
+        _idle.Token.Register(() => Trip($"produced no output for {idleTimeout}"));
+        if (timeout != null) _timeout.CancelAfter(timeout.Value);
+        Kick();
+    }
+
+    public static int Retries =>
+        int.TryParse(Environment.GetEnvironmentVariable("MBSS_CHILD_RETRIES"), out var retries) ? retries : 2;
+
+    // MBSS_<STAGE>_TIMEOUT limits the whole run, MBSS_<STAGE>_IDLE_TIMEOUT the time between two lines of output
+    public static Watchdog ForStage(Process process, string name, string stage, TimeSpan? defaultIdleTimeout = null)
+    {
+        return new Watchdog(process, name, Durations.FromEnvironment($"MBSS_{stage}_TIMEOUT"),
+            Durations.FromEnvironment($"MBSS_{stage}_IDLE_TIMEOUT", defaultIdleTimeout));
+    }
+
+    public void Kick()
+    {
+        if (_idleTimeout != null && _tripped == null) _idle.CancelAfter(_idleTimeout.Value);
+    }
+
+    public void Output(string line)
+    {
+        lock (_tail)
+        {
+            _tail.Enqueue(line.TrimEnd());
+            if (_tail.Count > TailLines) _tail.Dequeue();
+        }
+
+        Kick();
+    }
+
+    public void ThrowIfTripped()
+    {
+        if (_tripped == null) return;
+
+        string[] tail;
+        lock (_tail) tail = _tail.ToArray();
+        if (tail.Length > 0)
+        {
+            AnsiConsole.MarkupLine($"[yellow]Last output of {_name}:[/]");
+            foreach (var line in tail) AnsiConsole.WriteLine(line);
+        }
+
+        throw new TimeoutException($"{_name} {_tripped} and was killed!");
+    }
+
+    public void Dispose()
+    {
+        _timeout.Dispose();
+        _idle.Dispose();
+    }
+
+    private void Trip(string reason)
+    {
+        if (Interlocked.CompareExchange(ref _tripped, reason, null) != null) return;
+
+        try
+        {
+            _process.Kill(true);
+        }
+        catch (InvalidOperationException)
+        {
+            // Exited on its own in the meantime
+        }
+    }
+}
@@ -0,0 +1,115 @@
+﻿using System.Text;
+using LibGit2Sharp;
+using Spectre.Console;
+
+namespace MBSS;
+
+internal static class Workspace
+{
+    // Files on main that MBSS reads or updates in place, bare repositories get them copied into the work directory
+    private static readonly string[] MainFiles = { "versions.json", ".gitignore", ".gitattributes", "CHANGELOG.md" };
+
+    public static string RepositoryPath { get; } = Directory.GetCurrentDirectory();
+    public static bool IsBare { get; private set; }
+
+    public static void Open()
+    {
+        if (!Repository.IsValid(RepositoryPath)) return;
+
+        using var repo = new Repository(RepositoryPath);
+        if (!repo.Info.IsBare) return;
+
+        // Bare repositories have no checkout, versions are stripped into a scratch directory and committed from there
+        IsBare = true;
+        var workPath = Environment.GetEnvironmentVariable("MBSS_WORK_DIR") ??
+                       Path.Combine(Path.GetTempPath(), "mbss-work");
+        Directory.CreateDirectory(workPath);
+        Directory.SetCurrentDirectory(workPath);
+        AnsiConsole.MarkupLine($"[yellow]Operating on bare repository {RepositoryPath}, working in {workPath}[/]");
+
+        var tip = VersionTree.MainTip(repo);
+        foreach (var file in MainFiles)
+        {
+            if (tip?[file]?.Target is Blob blob) File.WriteAllText(file, blob.GetContentText());
+            else if (File.Exists(file)) File.Delete(file);
+        }
+    }
+
+    // Anything MBSS deletes has to live in a repository it manages, not wherever it happened to be started
+    public static bool IsManaged()
+    {
+        if (!IsBare && !Repository.IsValid(RepositoryPath)) return false;
+        return File.Exists("versions.json") || File.Exists(".mbss");
+    }
+
+    public static bool VersionExists(BeatSaberVersion version, string versionPath)
+    {
+        if (Directory.Exists(versionPath)) return true;
+
+        // Same answer as MBSS exists, a version whose branch was pushed counts as mirrored in either commit mode
+        using var repo = new Repository(RepositoryPath);
+        if (OrphanBranches.Exists(repo, version.Version)) return true;
+        return IsBare && VersionTree.Find(repo, version.Version) != null;
+    }
+
+    public static bool CommitToObjectDatabase(Repository repo, string message, Signature author,
+        IEnumerable<string> paths)
+    {
+        var tip = VersionTree.MainTip(repo);
+        var definition = tip == null ? new TreeDefinition() : TreeDefinition.From(tip.Tree);
+
+        foreach (var path in paths)
+        {
+            // Paths that are gone from disk are removed from the tree
+            if (!Directory.Exists(path) && !File.Exists(path))
+            {
+                definition.Remove(TreePath(path));
+                continue;
+            }
+
+            var files = Directory.Exists(path) && !FileCopy.IsSymlink(path)
+                ? FileCopy.EnumerateFiles(path).ToList()
+                : new List<string> { path };
+            if (Directory.Exists(path)) definition.Remove(TreePath(path));
+
+            // Files that are identical to an earlier version are already in the object database and skip compression
+            var ids = Parallelism.HashBlobs(files);
+            for (var i = 0; i < files.Count; i++)
+            {
+                var id = ids[i];
+                if (!repo.ObjectDatabase.Contains(id))
+                {
+                    using var content = OpenBlobContent(files[i]);
+                    id = repo.ObjectDatabase.CreateBlob(content).Id;
+                }
+
+                var mode = FileCopy.IsSymlink(files[i]) ? Mode.SymbolicLink
+                    : FileCopy.IsExecutable(files[i]) ? Mode.ExecutableFile
+                    : Mode.NonExecutableFile;
+                definition.Add(TreePath(files[i]), id, mode);
+            }
+        }
+
+        var tree = repo.ObjectDatabase.CreateTree(definition);
+        if (tip != null && tree.Id == tip.Tree.Id) return false; // No changes, skip
+
+        var parents = tip == null ? Array.Empty<Commit>() : new[] { tip };
+        var commit = repo.ObjectDatabase.CreateCommit(author, author, message, tree, parents, false);
+        repo.Refs.Add("refs/heads/main", commit.Id, true);
+        return true;
+    }
+
+    // Git stores a symlink as a blob holding its target
+    public static Stream OpenBlobContent(string path)
+    {
+        var linkTarget = new FileInfo(path).LinkTarget;
+        return linkTarget != null
+            ? new MemoryStream(Encoding.UTF8.GetBytes(linkTarget.Replace('\\', '/')))
+            : File.OpenRead(path);
+    }
+
+    private static string TreePath(string path)
+    {
+        return Path.GetRelativePath(Directory.GetCurrentDirectory(), path).Replace('\\', '/');
+    }
+}